@@ -0,0 +1,9 @@
+CREATE INDEX stories_owner_created_at_id_index
+    ON stories
+    USING btree(owner, created_at, id)
+    WHERE deleted_at IS NULL;
+
+CREATE INDEX tasks_story_id_created_at_id_index
+    ON tasks
+    USING btree(story_id, created_at, id)
+    WHERE deleted_at IS NULL;
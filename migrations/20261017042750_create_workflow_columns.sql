@@ -0,0 +1,21 @@
+CREATE TABLE workflow_columns
+(
+    id uuid default gen_random_uuid() primary key,
+    story_id uuid not null,
+    name varchar(100) not null,
+    status varchar(100) not null,
+    position integer not null,
+    created_at timestamptz not null default now()
+);
+
+ALTER TABLE ONLY workflow_columns
+    ADD CONSTRAINT workflow_columns_story_id_fkey
+    FOREIGN KEY (story_id)
+    REFERENCES stories(id)
+    ON DELETE CASCADE;
+
+CREATE UNIQUE INDEX workflow_columns_story_id_name_index
+    ON workflow_columns
+    USING btree(story_id, name);
+
+ALTER TABLE tasks ADD COLUMN workflow_column varchar(100);
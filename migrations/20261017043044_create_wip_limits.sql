@@ -0,0 +1,19 @@
+CREATE TABLE wip_limits
+(
+    id uuid default gen_random_uuid() primary key,
+    story_id uuid not null,
+    workflow_column varchar(100),
+    max_tasks integer not null check (max_tasks > 0),
+    created_at timestamptz not null default now(),
+    updated_at timestamptz not null default now()
+);
+
+ALTER TABLE ONLY wip_limits
+    ADD CONSTRAINT wip_limits_story_id_fkey
+    FOREIGN KEY (story_id)
+    REFERENCES stories(id)
+    ON DELETE CASCADE;
+
+CREATE UNIQUE INDEX wip_limits_story_id_workflow_column_index
+    ON wip_limits
+    USING btree(story_id, COALESCE(workflow_column, ''));
@@ -0,0 +1,8 @@
+ALTER TABLE stories ADD COLUMN deletion_id uuid;
+
+ALTER TABLE tasks ADD COLUMN deletion_id uuid;
+
+CREATE INDEX tasks_story_id_deletion_id_index
+    ON tasks
+    USING btree(story_id, deletion_id)
+    WHERE deletion_id IS NOT NULL;
@@ -0,0 +1,9 @@
+CREATE INDEX stories_deleted_at_index
+    ON stories
+    USING btree(deleted_at)
+    WHERE deleted_at IS NOT NULL;
+
+CREATE INDEX tasks_deleted_at_index
+    ON tasks
+    USING btree(deleted_at)
+    WHERE deleted_at IS NOT NULL;
@@ -0,0 +1,6 @@
+ALTER TABLE tasks ADD COLUMN due_at timestamptz;
+
+CREATE INDEX tasks_due_at_id_index
+    ON tasks
+    USING btree(due_at, id)
+    WHERE deleted_at IS NULL AND due_at IS NOT NULL;
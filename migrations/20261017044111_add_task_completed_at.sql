@@ -0,0 +1,3 @@
+ALTER TABLE tasks ADD COLUMN completed_at timestamptz;
+
+UPDATE tasks SET completed_at = updated_at WHERE status = 'complete';
@@ -0,0 +1,12 @@
+ALTER TABLE tasks ADD COLUMN priority smallint not null default 2
+    CONSTRAINT tasks_priority_check CHECK (priority BETWEEN 0 AND 3);
+
+CREATE INDEX tasks_story_id_priority_created_at_id_index
+    ON tasks
+    USING btree(story_id, priority, created_at, id)
+    WHERE deleted_at IS NULL;
+
+CREATE INDEX tasks_story_id_due_at_created_at_id_index
+    ON tasks
+    USING btree(story_id, (COALESCE(due_at, 'infinity')), created_at, id)
+    WHERE deleted_at IS NULL;
@@ -0,0 +1,16 @@
+-- Ranks are base-36 strings compared bytewise; existing tasks keep creation order.
+ALTER TABLE tasks ADD COLUMN rank varchar(100) COLLATE "C";
+
+UPDATE tasks SET rank = ranked.rank
+FROM (
+    SELECT id, lpad(row_number() OVER (PARTITION BY story_id ORDER BY created_at, id)::text, 8, '0') || 'i' AS rank
+    FROM tasks
+) ranked
+WHERE tasks.id = ranked.id;
+
+ALTER TABLE tasks ALTER COLUMN rank SET NOT NULL;
+
+CREATE INDEX tasks_story_id_rank_created_at_id_index
+    ON tasks
+    USING btree(story_id, rank, created_at, id)
+    WHERE deleted_at IS NULL;
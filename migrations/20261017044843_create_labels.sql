@@ -0,0 +1,36 @@
+CREATE TABLE labels
+(
+    id uuid default gen_random_uuid() primary key,
+    owner varchar(100) not null,
+    name varchar(100) not null,
+    color varchar(7) not null,
+    created_at timestamptz not null default now()
+);
+
+CREATE UNIQUE INDEX labels_owner_name_index
+    ON labels
+    USING btree(owner, lower(name));
+
+CREATE TABLE task_labels
+(
+    task_id uuid not null,
+    label_id uuid not null,
+    created_at timestamptz not null default now(),
+    primary key (task_id, label_id)
+);
+
+ALTER TABLE ONLY task_labels
+    ADD CONSTRAINT task_labels_task_id_fkey
+    FOREIGN KEY (task_id)
+    REFERENCES tasks(id)
+    ON DELETE CASCADE;
+
+ALTER TABLE ONLY task_labels
+    ADD CONSTRAINT task_labels_label_id_fkey
+    FOREIGN KEY (label_id)
+    REFERENCES labels(id)
+    ON DELETE CASCADE;
+
+CREATE INDEX task_labels_label_id_task_id_index
+    ON task_labels
+    USING btree(label_id, task_id);
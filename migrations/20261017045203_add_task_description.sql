@@ -0,0 +1,2 @@
+ALTER TABLE tasks ADD COLUMN description text not null default ''
+    CONSTRAINT tasks_description_length_check CHECK (char_length(description) <= 10000);
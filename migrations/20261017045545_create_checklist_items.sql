@@ -0,0 +1,28 @@
+CREATE TABLE checklist_items
+(
+    id uuid default gen_random_uuid() primary key,
+    task_id uuid not null,
+    name varchar(100) not null,
+    done boolean not null default false,
+    rank varchar(100) COLLATE "C" not null,
+    created_at timestamptz not null default now(),
+    updated_at timestamptz not null default now(),
+    deleted_at timestamptz,
+    deletion_id uuid
+);
+
+ALTER TABLE ONLY checklist_items
+    ADD CONSTRAINT checklist_items_task_id_fkey
+    FOREIGN KEY (task_id)
+    REFERENCES tasks(id)
+    ON DELETE CASCADE;
+
+CREATE INDEX checklist_items_task_id_rank_index
+    ON checklist_items
+    USING btree(task_id, rank)
+    WHERE deleted_at IS NULL;
+
+CREATE INDEX checklist_items_deletion_id_index
+    ON checklist_items
+    USING btree(deletion_id)
+    WHERE deletion_id IS NOT NULL;
@@ -0,0 +1,24 @@
+CREATE TABLE task_dependencies
+(
+    task_id uuid not null,
+    blocker_id uuid not null,
+    created_at timestamptz not null default now(),
+    primary key (task_id, blocker_id),
+    CHECK (task_id <> blocker_id)
+);
+
+ALTER TABLE ONLY task_dependencies
+    ADD CONSTRAINT task_dependencies_task_id_fkey
+    FOREIGN KEY (task_id)
+    REFERENCES tasks(id)
+    ON DELETE CASCADE;
+
+ALTER TABLE ONLY task_dependencies
+    ADD CONSTRAINT task_dependencies_blocker_id_fkey
+    FOREIGN KEY (blocker_id)
+    REFERENCES tasks(id)
+    ON DELETE CASCADE;
+
+CREATE INDEX task_dependencies_blocker_id_task_id_index
+    ON task_dependencies
+    USING btree(blocker_id, task_id);
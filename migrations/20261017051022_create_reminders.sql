@@ -0,0 +1,28 @@
+CREATE TABLE reminders
+(
+    id uuid default gen_random_uuid() primary key,
+    task_id uuid not null,
+    remind_at timestamptz,
+    offset_secs bigint,
+    created_at timestamptz not null default now(),
+    sent_at timestamptz,
+    attempts int not null default 0,
+    last_error text,
+    CONSTRAINT reminders_time_check CHECK ((remind_at IS NULL) <> (offset_secs IS NULL)),
+    CONSTRAINT reminders_offset_check CHECK (offset_secs >= 0)
+);
+
+ALTER TABLE ONLY reminders
+    ADD CONSTRAINT reminders_task_id_fkey
+    FOREIGN KEY (task_id)
+    REFERENCES tasks(id)
+    ON DELETE CASCADE;
+
+CREATE INDEX reminders_task_id_index
+    ON reminders
+    USING btree(task_id);
+
+CREATE INDEX reminders_pending_remind_at_index
+    ON reminders
+    USING btree(remind_at)
+    WHERE sent_at IS NULL;
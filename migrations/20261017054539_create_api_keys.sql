@@ -0,0 +1,16 @@
+CREATE TABLE api_keys
+(
+    id uuid default gen_random_uuid() primary key,
+    owner varchar(100) not null,
+    name varchar(100) not null,
+    salt bytea not null,
+    key_hash bytea not null,
+    created_at timestamptz not null default now(),
+    expires_at timestamptz,
+    revoked_at timestamptz,
+    last_used_at timestamptz
+);
+
+CREATE INDEX api_keys_owner_index
+    ON api_keys
+    USING btree(owner);
@@ -0,0 +1,19 @@
+CREATE TABLE story_members
+(
+    story_id uuid not null,
+    member varchar(100) not null,
+    role varchar(10) not null check (role IN ('viewer', 'editor', 'admin')),
+    created_at timestamptz not null default now(),
+    updated_at timestamptz not null default now(),
+    primary key (story_id, member)
+);
+
+ALTER TABLE ONLY story_members
+    ADD CONSTRAINT story_members_story_id_fkey
+    FOREIGN KEY (story_id)
+    REFERENCES stories(id)
+    ON DELETE CASCADE;
+
+CREATE INDEX story_members_member_index
+    ON story_members
+    USING btree(member);
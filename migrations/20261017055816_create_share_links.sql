@@ -0,0 +1,26 @@
+CREATE TABLE share_links
+(
+    id uuid default gen_random_uuid() primary key,
+    story_id uuid not null,
+    token_hash bytea not null,
+    created_by varchar(100) not null,
+    created_at timestamptz not null default now(),
+    expires_at timestamptz,
+    revoked_at timestamptz,
+    access_count bigint not null default 0,
+    last_accessed_at timestamptz
+);
+
+ALTER TABLE ONLY share_links
+    ADD CONSTRAINT share_links_story_id_fkey
+    FOREIGN KEY (story_id)
+    REFERENCES stories(id)
+    ON DELETE CASCADE;
+
+CREATE UNIQUE INDEX share_links_token_hash_index
+    ON share_links
+    USING btree(token_hash);
+
+CREATE INDEX share_links_story_id_index
+    ON share_links
+    USING btree(story_id);
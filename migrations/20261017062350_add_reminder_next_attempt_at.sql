@@ -0,0 +1,2 @@
+-- Failed deliveries wait until next_attempt_at before they are retried.
+ALTER TABLE reminders ADD COLUMN next_attempt_at timestamptz;
@@ -0,0 +1,9 @@
+#!/bin/bash
+
+grpcurl -plaintext \
+  -H "authorization: Bearer ${TODOS_TOKEN}" \
+  -import-path ./proto \
+  -proto ./proto/todos/v1/todos.proto \
+  -d '{"owner": "github.com/carp-cobain", "name": "nightly import", "expires_at": "2027-01-01T00:00:00Z"}' \
+  "[::]:9090" \
+  todos.v1.TodosService/CreateApiKey
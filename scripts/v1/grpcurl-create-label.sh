@@ -0,0 +1,9 @@
+#!/bin/bash
+
+grpcurl -plaintext \
+  -H "authorization: Bearer ${TODOS_TOKEN}" \
+  -import-path ./proto \
+  -proto ./proto/todos/v1/todos.proto \
+  -d '{"name": "bug", "color": "#d73a4a"}' \
+  "[::]:9090" \
+  todos.v1.TodosService/CreateLabel
@@ -0,0 +1,9 @@
+#!/bin/bash
+
+grpcurl -plaintext \
+  -H "authorization: Bearer ${TODOS_TOKEN}" \
+  -import-path ./proto \
+  -proto ./proto/todos/v1/todos.proto \
+  -d '{"reminder_id": "2b7c9e41-8f3a-4d6e-9c05-1a2b3c4d5e6f"}' \
+  "[::]:9090" \
+  todos.v1.TodosService/DeleteReminder
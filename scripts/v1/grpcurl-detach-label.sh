@@ -0,0 +1,9 @@
+#!/bin/bash
+
+grpcurl -plaintext \
+  -H "authorization: Bearer ${TODOS_TOKEN}" \
+  -import-path ./proto \
+  -proto ./proto/todos/v1/todos.proto \
+  -d '{"task_id": "477991c0-b0c7-4e5e-a4dc-21a74ac6cbec", "label_id": "9b2e4c1a-7d3f-4a8b-b6e5-2c1d0f9e8a70"}' \
+  "[::]:9090" \
+  todos.v1.TodosService/DetachLabel
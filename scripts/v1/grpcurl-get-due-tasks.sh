@@ -0,0 +1,9 @@
+#!/bin/bash
+
+grpcurl -plaintext \
+  -H "authorization: Bearer ${TODOS_TOKEN}" \
+  -import-path ./proto \
+  -proto ./proto/todos/v1/todos.proto \
+  -d '{"due_before": "2026-12-31T00:00:00Z", "include_overdue": true}' \
+  "[::]:9090" \
+  todos.v1.TodosService/GetDueTasks
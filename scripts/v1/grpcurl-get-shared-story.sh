@@ -0,0 +1,8 @@
+#!/bin/bash
+
+grpcurl -plaintext \
+  -import-path ./proto \
+  -proto ./proto/todos/v1/todos.proto \
+  -d "{\"token\": \"${TODOS_SHARE_TOKEN}\"}" \
+  "[::]:9090" \
+  todos.v1.SharedStoryService/GetSharedStory
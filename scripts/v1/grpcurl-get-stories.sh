@@ -1,8 +1,9 @@
 #!/bin/bash
 
 grpcurl -plaintext \
+  -H "authorization: Bearer ${TODOS_TOKEN}" \
   -import-path ./proto \
   -proto ./proto/todos/v1/todos.proto \
-  -d '{"owner": "github.com/carp-cobain"}' \
+  -d '{}' \
   "[::]:9090" \
   todos.v1.TodosService/GetStories
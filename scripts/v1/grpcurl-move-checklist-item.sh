@@ -0,0 +1,9 @@
+#!/bin/bash
+
+grpcurl -plaintext \
+  -H "authorization: Bearer ${TODOS_TOKEN}" \
+  -import-path ./proto \
+  -proto ./proto/todos/v1/todos.proto \
+  -d '{"item_id": "c3f1a2b4-5d6e-4f70-8a9b-0c1d2e3f4a5b", "after_item_id": "e7d6c5b4-a392-4817-b6a5-948372615f0e"}' \
+  "[::]:9090" \
+  todos.v1.TodosService/MoveChecklistItem
@@ -0,0 +1,9 @@
+#!/bin/bash
+
+grpcurl -plaintext \
+  -H "authorization: Bearer ${TODOS_TOKEN}" \
+  -import-path ./proto \
+  -proto ./proto/todos/v1/todos.proto \
+  -d '{"task_id": "477991c0-b0c7-4e5e-a4dc-21a74ac6cbec", "story_id": "5d1a0c7e-3b2f-4e8a-9c6d-8f4b2a1e7c30"}' \
+  "[::]:9090" \
+  todos.v1.TodosService/MoveTaskToStory
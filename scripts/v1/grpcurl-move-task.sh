@@ -0,0 +1,9 @@
+#!/bin/bash
+
+grpcurl -plaintext \
+  -H "authorization: Bearer ${TODOS_TOKEN}" \
+  -import-path ./proto \
+  -proto ./proto/todos/v1/todos.proto \
+  -d '{"task_id": "477991c0-b0c7-4e5e-a4dc-21a74ac6cbec", "before_task_id": "d2a8b4a3-0c4b-4a4e-9f5e-6a3f1c2b7e10"}' \
+  "[::]:9090" \
+  todos.v1.TodosService/MoveTask
@@ -0,0 +1,9 @@
+#!/bin/bash
+
+grpcurl -plaintext \
+  -H "authorization: Bearer ${TODOS_TOKEN}" \
+  -import-path ./proto \
+  -proto ./proto/todos/v1/todos.proto \
+  -d '{"task_id": "477991c0-b0c7-4e5e-a4dc-21a74ac6cbec", "blocker_task_id": "9d3f0a52-6c1e-4b8f-a7d2-3e5b1c0f8a64"}' \
+  "[::]:9090" \
+  todos.v1.TodosService/RemoveDependency
@@ -0,0 +1,9 @@
+#!/bin/bash
+
+grpcurl -plaintext \
+  -H "authorization: Bearer ${TODOS_TOKEN}" \
+  -import-path ./proto \
+  -proto ./proto/todos/v1/todos.proto \
+  -d '{"key_id": "8d3f1c27-5b6e-4a90-b1d2-7e4f6a8c9b03"}' \
+  "[::]:9090" \
+  todos.v1.TodosService/RevokeApiKey
@@ -0,0 +1,9 @@
+#!/bin/bash
+
+grpcurl -plaintext \
+  -H "authorization: Bearer ${TODOS_TOKEN}" \
+  -import-path ./proto \
+  -proto ./proto/todos/v1/todos.proto \
+  -d '{"link_id": "0e9d8c7b-6a5f-4e3d-8c2b-1a0f9e8d7c6b"}' \
+  "[::]:9090" \
+  todos.v1.TodosService/RevokeShareLink
@@ -0,0 +1,9 @@
+#!/bin/bash
+
+grpcurl -plaintext \
+  -H "authorization: Bearer ${TODOS_TOKEN}" \
+  -import-path ./proto \
+  -proto ./proto/todos/v1/todos.proto \
+  -d '{"story_id": "c6a1d0f2-3b4e-4f5a-9c8d-7e6f5a4b3c2d", "member": "github.com/octocat", "role": "STORY_ROLE_EDITOR"}' \
+  "[::]:9090" \
+  todos.v1.TodosService/ShareStory
@@ -0,0 +1,9 @@
+#!/bin/bash
+
+grpcurl -plaintext \
+  -H "authorization: Bearer ${TODOS_TOKEN}" \
+  -import-path ./proto \
+  -proto ./proto/todos/v1/todos.proto \
+  -d '{"item_id": "c3f1a2b4-5d6e-4f70-8a9b-0c1d2e3f4a5b", "done": true}' \
+  "[::]:9090" \
+  todos.v1.TodosService/ToggleChecklistItem
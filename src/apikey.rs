@@ -0,0 +1,308 @@
+use crate::auth::Caller;
+use crate::v1::entity::ApiKey;
+use crate::{Error, Result};
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use chrono::{DateTime, Utc};
+use rand::RngCore;
+use sha2::{Digest, Sha256};
+use sqlx::postgres::PgPool;
+use sqlx::Row;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, PoisonError, RwLock};
+use subtle::ConstantTimeEq;
+use tokio::time::{self, Duration};
+use uuid::Uuid;
+
+/// Random bytes in a key secret.
+const SECRET_LEN: usize = 32;
+
+/// Random bytes in the salt hashed with each secret.
+const SALT_LEN: usize = 16;
+
+/// A newly minted key. The key itself is only returned to whoever minted it;
+/// only its salt and hash are stored.
+pub struct MintedKey {
+    pub key_id: Uuid,
+    /// The value callers send as `x-api-key`: the key id and a secret, dot separated.
+    pub key: String,
+    pub salt: Vec<u8>,
+    pub key_hash: Vec<u8>,
+}
+
+impl MintedKey {
+    pub fn generate() -> Self {
+        let mut rng = rand::thread_rng();
+        let mut secret = [0u8; SECRET_LEN];
+        rng.fill_bytes(&mut secret);
+        let mut salt = vec![0u8; SALT_LEN];
+        rng.fill_bytes(&mut salt);
+
+        let key_id = Uuid::new_v4();
+        let secret = URL_SAFE_NO_PAD.encode(secret);
+        Self {
+            key_id,
+            key: format!("{}.{}", key_id, secret),
+            key_hash: hash(&salt, &secret),
+            salt,
+        }
+    }
+}
+
+/// Salted SHA-256 of a key secret.
+fn hash(salt: &[u8], secret: &str) -> Vec<u8> {
+    let mut hasher = Sha256::new();
+    hasher.update(salt);
+    hasher.update(secret.as_bytes());
+    hasher.finalize().to_vec()
+}
+
+/// A key that has not been revoked or expired, as cached for verification.
+struct ActiveKey {
+    owner: String,
+    salt: Vec<u8>,
+    key_hash: Vec<u8>,
+    expires_at: Option<DateTime<Utc>>,
+}
+
+/// Verifies API keys against a cache of the active keys. Interceptors can't
+/// wait on the database, so the cache is reloaded on an interval, and the
+/// last-used times recorded while verifying are written back in one batch per
+/// interval. Keys minted or revoked on this replica are cached or evicted
+/// right away; other replicas pick them up on their next reload.
+pub struct ApiKeys {
+    active: RwLock<HashMap<Uuid, ActiveKey>>,
+    used: Mutex<HashMap<Uuid, DateTime<Utc>>>,
+    interval: Duration,
+}
+
+impl ApiKeys {
+    pub fn new(interval_secs: u64) -> Self {
+        Self {
+            active: RwLock::new(HashMap::new()),
+            used: Mutex::new(HashMap::new()),
+            interval: Duration::from_secs(interval_secs),
+        }
+    }
+}
+
+impl ApiKeys {
+    /// Verify a key and return the caller it identifies, the key's owner.
+    pub fn verify(&self, key: &str) -> Result<Caller> {
+        let invalid = |reason: &str| {
+            log::debug!("Rejected API key: {}", reason);
+            Error::Unauthenticated {
+                message: "invalid API key".to_string(),
+            }
+        };
+
+        let (key_id, secret) = key
+            .trim()
+            .split_once('.')
+            .ok_or_else(|| invalid("malformed key"))?;
+        let key_id = Uuid::parse_str(key_id).map_err(|_| invalid("malformed key id"))?;
+
+        let active = self.active.read().unwrap_or_else(PoisonError::into_inner);
+        let cached = active
+            .get(&key_id)
+            .ok_or_else(|| invalid("unknown, revoked or expired key"))?;
+        if !bool::from(hash(&cached.salt, secret).ct_eq(&cached.key_hash)) {
+            return Err(invalid("secret mismatch"));
+        }
+        let now = Utc::now();
+        if cached.expires_at.is_some_and(|at| at <= now) {
+            return Err(invalid("expired key"));
+        }
+
+        self.used
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(key_id, now);
+        Ok(Caller::new(&cached.owner))
+    }
+
+    /// Cache a key minted on this replica, so it works before the next reload.
+    pub fn insert(&self, api_key: &ApiKey, minted: &MintedKey) {
+        let key = ActiveKey {
+            owner: api_key.owner.clone(),
+            salt: minted.salt.clone(),
+            key_hash: minted.key_hash.clone(),
+            expires_at: api_key.expires_at,
+        };
+        self.active
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(api_key.key_id, key);
+    }
+
+    /// Drop a key revoked on this replica, so it stops working before the next reload.
+    pub fn evict(&self, key_id: Uuid) {
+        self.active
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .remove(&key_id);
+    }
+
+    /// Reload the active keys, returning how many there are.
+    pub async fn refresh(&self, db: &PgPool) -> std::result::Result<usize, sqlx::Error> {
+        let sql = r#"
+            SELECT id, owner, salt, key_hash, expires_at FROM api_keys
+            WHERE revoked_at IS NULL AND (expires_at IS NULL OR expires_at > now())
+        "#;
+        let rows = sqlx::query(sql).fetch_all(db).await?;
+
+        let active = rows
+            .iter()
+            .map(|row| {
+                let key = ActiveKey {
+                    owner: row.try_get("owner")?,
+                    salt: row.try_get("salt")?,
+                    key_hash: row.try_get("key_hash")?,
+                    expires_at: row.try_get("expires_at")?,
+                };
+                Ok((row.try_get("id")?, key))
+            })
+            .collect::<std::result::Result<HashMap<Uuid, ActiveKey>, sqlx::Error>>()?;
+        let count = active.len();
+        *self.active.write().unwrap_or_else(PoisonError::into_inner) = active;
+
+        Ok(count)
+    }
+
+    /// Write back the last-used times recorded since the previous flush in a
+    /// single update. Times that fail to write are kept for the next flush.
+    pub async fn flush(&self, db: &PgPool) -> std::result::Result<u64, sqlx::Error> {
+        let used = std::mem::take(&mut *self.used.lock().unwrap_or_else(PoisonError::into_inner));
+        if used.is_empty() {
+            return Ok(0);
+        }
+        let (key_ids, used_at): (Vec<Uuid>, Vec<DateTime<Utc>>) = used.into_iter().unzip();
+
+        let sql = r#"
+            UPDATE api_keys SET last_used_at = GREATEST(last_used_at, u.used_at)
+            FROM UNNEST($1::uuid[], $2::timestamptz[]) AS u(id, used_at)
+            WHERE api_keys.id = u.id
+        "#;
+        let result = sqlx::query(sql)
+            .bind(&key_ids)
+            .bind(&used_at)
+            .execute(db)
+            .await;
+
+        match result {
+            Ok(result) => Ok(result.rows_affected()),
+            Err(err) => {
+                let mut used = self.used.lock().unwrap_or_else(PoisonError::into_inner);
+                for (key_id, at) in key_ids.into_iter().zip(used_at) {
+                    used.entry(key_id).or_insert(at);
+                }
+                Err(err)
+            }
+        }
+    }
+
+    /// Periodically write back last-used times and reload the active keys.
+    pub async fn run(self: Arc<Self>, db: Arc<PgPool>) {
+        log::info!("Starting API key refresh job");
+        let db = db.as_ref();
+        loop {
+            time::sleep(self.interval).await;
+            if let Err(err) = self.flush(db).await {
+                log::error!("API key last-used update failed: {}", err.to_string());
+            }
+            if let Err(err) = self.refresh(db).await {
+                log::error!("API key refresh failed: {}", err.to_string());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cache(minted: &MintedKey, expires_at: Option<DateTime<Utc>>) -> ApiKeys {
+        let keys = ApiKeys::new(30);
+        keys.active.write().unwrap().insert(
+            minted.key_id,
+            ActiveKey {
+                owner: "github.com/carp-cobain/batch".to_string(),
+                salt: minted.salt.clone(),
+                key_hash: minted.key_hash.clone(),
+                expires_at,
+            },
+        );
+        keys
+    }
+
+    #[test]
+    fn minted_keys_are_salted() {
+        let a = MintedKey::generate();
+        let b = MintedKey::generate();
+        assert_ne!(a.key, b.key);
+        assert_ne!(a.salt, b.salt);
+        assert_eq!(a.key_hash.len(), 32);
+        let (key_id, secret) = a.key.split_once('.').unwrap();
+        assert_eq!(key_id, a.key_id.to_string());
+        assert_eq!(hash(&a.salt, secret), a.key_hash);
+        assert_ne!(hash(&b.salt, secret), a.key_hash);
+    }
+
+    #[test]
+    fn verify_key_and_record_use() {
+        let minted = MintedKey::generate();
+        let keys = cache(&minted, None);
+        assert!(keys.used.lock().unwrap().is_empty());
+
+        let caller = keys.verify(&minted.key).unwrap();
+        assert_eq!(caller, Caller::new("github.com/carp-cobain/batch"));
+        assert!(keys.used.lock().unwrap().contains_key(&minted.key_id));
+    }
+
+    #[test]
+    fn verify_rejects_bad_keys() {
+        let minted = MintedKey::generate();
+        let keys = cache(&minted, None);
+        let other = MintedKey::generate();
+        let (_, other_secret) = other.key.split_once('.').unwrap();
+        for key in [
+            String::new(),
+            "not-a-key".to_string(),
+            format!("not-a-uuid.{}", other_secret),
+            format!("{}.{}", minted.key_id, other_secret),
+            other.key.clone(),
+        ] {
+            let err = keys.verify(&key).unwrap_err();
+            assert!(matches!(err, Error::Unauthenticated { .. }), "{}", key);
+        }
+        assert!(keys.used.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn insert_and_evict_keys() {
+        let minted = MintedKey::generate();
+        let api_key = ApiKey {
+            key_id: minted.key_id,
+            owner: "github.com/carp-cobain/batch".to_string(),
+            name: "import".to_string(),
+            created_at: Utc::now(),
+            expires_at: None,
+            revoked_at: None,
+            last_used_at: None,
+        };
+        let keys = ApiKeys::new(30);
+        assert!(keys.verify(&minted.key).is_err());
+        keys.insert(&api_key, &minted);
+        assert!(keys.verify(&minted.key).is_ok());
+        keys.evict(minted.key_id);
+        assert!(keys.verify(&minted.key).is_err());
+    }
+
+    #[test]
+    fn verify_rejects_expired_keys() {
+        let minted = MintedKey::generate();
+        let keys = cache(&minted, Some(Utc::now() - chrono::Duration::seconds(1)));
+        assert!(keys.verify(&minted.key).is_err());
+    }
+}
@@ -9,6 +9,12 @@ use std::sync::Arc;
 #[derive(Debug)]
 pub struct Config {
     pub grpc_listen_addr: SocketAddr,
+    pub jwt_secret: Option<String>,
+    pub jwks_path: Option<String>,
+    pub jwt_issuer: Option<String>,
+    pub jwt_audience: Option<String>,
+    pub admin_subjects: Vec<String>,
+    pub api_key_refresh_secs: u64,
     pub db_max_connections: u32,
     pub db_host: String,
     pub db_port: u16,
@@ -16,6 +22,27 @@ pub struct Config {
     pub db_password: String,
     pub db_database: String,
     pub db_schema: String,
+    pub purge_after_days: i64,
+    pub purge_batch_size: i64,
+    pub purge_interval_secs: u64,
+    pub reminder_notifier: String,
+    pub reminder_batch_size: i64,
+    pub reminder_interval_secs: u64,
+    pub reminder_timeout_secs: u64,
+    pub reminder_webhook_url: Option<String>,
+    pub smtp_host: Option<String>,
+    pub smtp_port: u16,
+    pub smtp_starttls: bool,
+    pub smtp_user: Option<String>,
+    pub smtp_password: Option<String>,
+    pub smtp_from: String,
+    pub smtp_to: Option<String>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Config {
@@ -25,6 +52,22 @@ impl Config {
         let listen_addr = env::var("GRPC_LISTEN_ADDR").unwrap_or("0.0.0.0:9090".into());
         let grpc_listen_addr = listen_addr.parse().expect("Unable to parse listen_addr");
 
+        // auth settings: an HS256 shared secret and/or a JWKS file of RS256 keys
+        let jwt_secret = env::var("JWT_SECRET").ok();
+        let jwks_path = env::var("JWKS_PATH").ok();
+        let jwt_issuer = env::var("JWT_ISSUER").ok();
+        let jwt_audience = env::var("JWT_AUDIENCE").ok();
+        let admin_subjects = env::var("ADMIN_SUBJECTS")
+            .unwrap_or_default()
+            .split(',')
+            .map(|subject| subject.trim().to_owned())
+            .filter(|subject| !subject.is_empty())
+            .collect();
+        let api_key_refresh_secs = env::var("API_KEY_REFRESH_SECS")
+            .unwrap_or("30".to_owned())
+            .parse()
+            .expect("API_KEY_REFRESH_SECS could not be parsed");
+
         // database settings
         let db_max_connections = env::var("DB_MAX_CONNECTIONS")
             .unwrap_or("10".to_owned())
@@ -40,9 +83,60 @@ impl Config {
         let db_database = env::var("DB_NAME").expect("DB_NAME not set");
         let db_schema = env::var("DB_SCHEMA").expect("DB_SCHEMA not set");
 
+        // purge settings
+        let purge_after_days = env::var("PURGE_AFTER_DAYS")
+            .unwrap_or("30".to_owned())
+            .parse()
+            .expect("PURGE_AFTER_DAYS could not be parsed");
+        let purge_batch_size = env::var("PURGE_BATCH_SIZE")
+            .unwrap_or("500".to_owned())
+            .parse()
+            .expect("PURGE_BATCH_SIZE could not be parsed");
+        let purge_interval_secs = env::var("PURGE_INTERVAL_SECS")
+            .unwrap_or("3600".to_owned())
+            .parse()
+            .expect("PURGE_INTERVAL_SECS could not be parsed");
+
+        // reminder settings
+        let reminder_notifier = env::var("REMINDER_NOTIFIER").unwrap_or("log".to_owned());
+        let reminder_batch_size = env::var("REMINDER_BATCH_SIZE")
+            .unwrap_or("100".to_owned())
+            .parse()
+            .expect("REMINDER_BATCH_SIZE could not be parsed");
+        let reminder_interval_secs = env::var("REMINDER_INTERVAL_SECS")
+            .unwrap_or("30".to_owned())
+            .parse()
+            .expect("REMINDER_INTERVAL_SECS could not be parsed");
+        let reminder_timeout_secs = env::var("REMINDER_TIMEOUT_SECS")
+            .unwrap_or("10".to_owned())
+            .parse()
+            .expect("REMINDER_TIMEOUT_SECS could not be parsed");
+        let reminder_webhook_url = env::var("REMINDER_WEBHOOK_URL").ok();
+
+        // smtp settings, for the smtp reminder notifier
+        let smtp_host = env::var("SMTP_HOST").ok();
+        let smtp_port = env::var("SMTP_PORT")
+            .unwrap_or("25".to_owned())
+            .parse()
+            .expect("SMTP_PORT could not be parsed");
+        let smtp_starttls = env::var("SMTP_STARTTLS")
+            .unwrap_or("false".to_owned())
+            .parse()
+            .expect("SMTP_STARTTLS could not be parsed");
+        let smtp_user = env::var("SMTP_USER").ok();
+        let smtp_password = env::var("SMTP_PASS").ok();
+        let smtp_from = env::var("SMTP_FROM").unwrap_or("todos@localhost".to_owned());
+        let smtp_to = env::var("SMTP_TO").ok();
+
         // Config
         Self {
             grpc_listen_addr,
+            jwt_secret,
+            jwks_path,
+            jwt_issuer,
+            jwt_audience,
+            admin_subjects,
+            api_key_refresh_secs,
             db_max_connections,
             db_host,
             db_port,
@@ -50,6 +144,21 @@ impl Config {
             db_password,
             db_database,
             db_schema,
+            purge_after_days,
+            purge_batch_size,
+            purge_interval_secs,
+            reminder_notifier,
+            reminder_batch_size,
+            reminder_interval_secs,
+            reminder_timeout_secs,
+            reminder_webhook_url,
+            smtp_host,
+            smtp_port,
+            smtp_starttls,
+            smtp_user,
+            smtp_password,
+            smtp_from,
+            smtp_to,
         }
     }
 
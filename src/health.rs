@@ -15,6 +15,12 @@ impl Health {
     }
 }
 
+impl Default for Health {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Health {
     /// Check that the repo can query the database
     pub(crate) async fn health_check_query(db: &PgPool) -> Result<(), Error> {
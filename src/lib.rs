@@ -1,7 +1,13 @@
 pub mod v1;
 
+pub mod apikey;
+pub mod auth;
 pub mod config;
 pub mod health;
+pub mod markdown;
+pub mod notify;
+pub mod purge;
+pub mod remind;
 pub mod validate;
 
 /// Project level error type
@@ -13,6 +19,18 @@ pub enum Error {
     InternalError { message: String },
     #[error("not found error: {message}")]
     NotFoundError { message: String },
+    #[error("failed precondition: {message}")]
+    FailedPrecondition { message: String },
+    #[error("permission denied: {message}")]
+    PermissionDenied { message: String },
+    #[error("unauthenticated: {message}")]
+    Unauthenticated { message: String },
+    #[error("failed precondition: {scope} limit of {limit} reached (current count {count})")]
+    WipLimitExceeded {
+        scope: String,
+        limit: i64,
+        count: i64,
+    },
 }
 
 /// Project level result type
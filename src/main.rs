@@ -1,7 +1,21 @@
 use todos::v1::{
-    api::Todos, proto::todos_service_server::TodosServiceServer, repo::Repo, service::Service,
+    api::{SharedStories, Todos},
+    proto::{
+        shared_story_service_server::SharedStoryServiceServer,
+        todos_service_server::TodosServiceServer,
+    },
+    repo::Repo,
+    service::Service,
+};
+use todos::{
+    apikey::ApiKeys,
+    auth::{AuthInterceptor, Authenticator},
+    config::Config,
+    health::Health,
+    notify,
+    purge::Purge,
+    remind::Remind,
 };
-use todos::{config::Config, health::Health};
 
 use sqlx::migrate::Migrator;
 use std::error::Error;
@@ -35,17 +49,44 @@ async fn main() -> Result<(), Box<dyn Error>> {
     let (reporter, health_service) = tonic_health::server::health_reporter();
     tokio::spawn(Health::check(reporter, Arc::clone(&pool)));
 
+    // Start purge task for expired soft-deleted rows
+    let purge = Purge::new(
+        config.purge_after_days,
+        config.purge_batch_size,
+        config.purge_interval_secs,
+    );
+    tokio::spawn(purge.run(Arc::clone(&pool)));
+
+    // Start reminder delivery task
+    let remind = Remind::new(
+        notify::from_config(&config),
+        config.reminder_batch_size,
+        config.reminder_interval_secs,
+    );
+    tokio::spawn(remind.run(Arc::clone(&pool)));
+
+    // Load API keys, then keep them fresh and write back last-used times in batches
+    let api_keys = Arc::new(ApiKeys::new(config.api_key_refresh_secs));
+    api_keys.refresh(&pool).await?;
+    tokio::spawn(Arc::clone(&api_keys).run(Arc::clone(&pool)));
+
     // Set up core logic for v1.
     let repo = Repo::new(Arc::clone(&pool));
     let service = Service::new(repo);
-    let api = Todos::new(service);
-    let todos_v1 = TodosServiceServer::new(api);
+    let api = Todos::new(service).with_api_keys(Arc::clone(&api_keys));
+    // Require a verified bearer token or API key on every v1 call; health checks stay open.
+    let auth = Arc::new(Authenticator::from_config(&config).with_api_keys(api_keys));
+    let todos_v1 = TodosServiceServer::with_interceptor(api, AuthInterceptor::new(auth));
+    // Share links are read with the share token alone, so this service has no interceptor.
+    let shared = SharedStories::new(Service::new(Repo::new(Arc::clone(&pool))));
+    let shared_v1 = SharedStoryServiceServer::new(shared);
 
     // Serve gRPC API
     log::info!("Server listening on {}", config.grpc_listen_addr);
     Server::builder()
         .add_service(health_service)
         .add_service(todos_v1)
+        .add_service(shared_v1)
         .serve(config.grpc_listen_addr)
         .await?;
 
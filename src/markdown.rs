@@ -0,0 +1,51 @@
+use pulldown_cmark::{html, Options, Parser};
+
+/// Render Markdown to HTML that is safe to embed in a page. Raw HTML in the
+/// source is sanitized rather than passed through.
+pub fn render(markdown: &str) -> String {
+    let options = Options::ENABLE_TABLES
+        | Options::ENABLE_STRIKETHROUGH
+        | Options::ENABLE_TASKLISTS
+        | Options::ENABLE_FOOTNOTES;
+    let parser = Parser::new_ext(markdown, options);
+
+    let mut unsafe_html = String::with_capacity(markdown.len() * 3 / 2);
+    html::push_html(&mut unsafe_html, parser);
+
+    ammonia::clean(&unsafe_html)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn render_markdown() {
+        let html = render("# Title\n\nSome *emphasis* and `code`.");
+        assert_eq!(
+            html,
+            "<h1>Title</h1>\n<p>Some <em>emphasis</em> and <code>code</code>.</p>\n"
+        );
+    }
+
+    #[test]
+    fn render_empty() {
+        assert_eq!(render(""), "");
+    }
+
+    #[test]
+    fn render_strips_scripts() {
+        let html = render("hi <script>alert(1)</script> <a href=\"javascript:alert(1)\">x</a>");
+        assert!(!html.contains("<script"));
+        assert!(!html.contains("javascript:"));
+    }
+
+    #[test]
+    fn render_links_are_safe() {
+        let html = render("[docs](https://example.com)");
+        assert_eq!(
+            html,
+            "<p><a href=\"https://example.com\" rel=\"noopener noreferrer\">docs</a></p>\n"
+        );
+    }
+}
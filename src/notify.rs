@@ -0,0 +1,234 @@
+use crate::config::Config;
+use crate::{Error, Result};
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use lettre::message::{header::ContentType, Mailbox};
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+use std::sync::Arc;
+use std::time::Duration;
+use uuid::Uuid;
+
+/// A reminder that is due for delivery.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Notification {
+    pub reminder_id: Uuid,
+    pub task_id: Uuid,
+    pub task_name: String,
+    pub story_id: Uuid,
+    pub owner: String,
+    pub due_at: Option<DateTime<Utc>>,
+    pub fire_at: DateTime<Utc>,
+}
+
+impl Notification {
+    /// Plain text message body.
+    fn text(&self) -> String {
+        let due = match self.due_at {
+            Some(due_at) => format!("due {}", rfc3339(due_at)),
+            None => "no due date".to_string(),
+        };
+        format!(
+            "Reminder: {} ({})\ntask: {}",
+            self.task_name, due, self.task_id
+        )
+    }
+
+    /// Email Message-ID, the same for every delivery of a reminder.
+    fn message_id(&self, domain: &str) -> String {
+        format!("<reminder-{}@{}>", self.reminder_id, domain)
+    }
+
+    /// JSON payload for webhooks.
+    fn json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "reminder_id": self.reminder_id.to_string(),
+            "task_id": self.task_id.to_string(),
+            "task_name": self.task_name,
+            "story_id": self.story_id.to_string(),
+            "owner": self.owner,
+            "due_at": self.due_at.map(rfc3339),
+            "fire_at": rfc3339(self.fire_at),
+        })
+    }
+}
+
+fn rfc3339(value: DateTime<Utc>) -> String {
+    value.to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+/// Delivers reminder notifications.
+#[tonic::async_trait]
+pub trait Notifier: Send + Sync {
+    async fn notify(&self, notification: &Notification) -> Result<()>;
+}
+
+/// Create the notifier selected in config. Panics on incomplete settings,
+/// like the rest of the config.
+pub fn from_config(config: &Config) -> Arc<dyn Notifier> {
+    match config.reminder_notifier.as_str() {
+        "log" => Arc::new(LogNotifier),
+        "webhook" => Arc::new(WebhookNotifier::new(
+            config
+                .reminder_webhook_url
+                .clone()
+                .expect("REMINDER_WEBHOOK_URL not set"),
+            Duration::from_secs(config.reminder_timeout_secs),
+        )),
+        "smtp" => Arc::new(SmtpNotifier::new(config)),
+        other => panic!("unknown REMINDER_NOTIFIER: {}", other),
+    }
+}
+
+/// Writes notifications to the server log.
+pub struct LogNotifier;
+
+#[tonic::async_trait]
+impl Notifier for LogNotifier {
+    async fn notify(&self, notification: &Notification) -> Result<()> {
+        log::info!(
+            "Reminder {} for {}: {}",
+            notification.reminder_id,
+            notification.owner,
+            notification.text()
+        );
+        Ok(())
+    }
+}
+
+/// Posts notifications as JSON to a webhook. The reminder id doubles as an
+/// idempotency key, so receivers can drop a repeated delivery. Requests time
+/// out, since delivery holds the reminder row locked.
+pub struct WebhookNotifier {
+    client: reqwest::Client,
+    url: String,
+}
+
+impl WebhookNotifier {
+    pub fn new(url: String, timeout: Duration) -> Self {
+        let client = reqwest::Client::builder()
+            .connect_timeout(timeout)
+            .timeout(timeout)
+            .build()
+            .expect("webhook client could not be built");
+        Self { client, url }
+    }
+}
+
+#[tonic::async_trait]
+impl Notifier for WebhookNotifier {
+    async fn notify(&self, notification: &Notification) -> Result<()> {
+        self.client
+            .post(&self.url)
+            .header("Idempotency-Key", notification.reminder_id.to_string())
+            .json(&notification.json())
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|err| Error::InternalError {
+                message: format!("webhook delivery failed: {}", err),
+            })?;
+        Ok(())
+    }
+}
+
+/// Emails notifications. Owners that are email addresses get their own
+/// reminders; everything else goes to the configured recipient. The
+/// Message-ID is derived from the reminder id, so a repeated delivery is the
+/// same message to receivers that drop duplicates.
+pub struct SmtpNotifier {
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+    from: Mailbox,
+    to: Option<Mailbox>,
+}
+
+impl SmtpNotifier {
+    pub fn new(config: &Config) -> Self {
+        let host = config.smtp_host.as_deref().expect("SMTP_HOST not set");
+        let mut builder = if config.smtp_starttls {
+            AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(host)
+                .expect("SMTP_HOST could not be used for STARTTLS")
+        } else {
+            AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(host)
+        };
+        builder = builder
+            .port(config.smtp_port)
+            .timeout(Some(Duration::from_secs(config.reminder_timeout_secs)));
+        if let (Some(user), Some(pass)) = (&config.smtp_user, &config.smtp_password) {
+            builder = builder.credentials(Credentials::new(user.clone(), pass.clone()));
+        }
+
+        Self {
+            transport: builder.build(),
+            from: config
+                .smtp_from
+                .parse()
+                .expect("SMTP_FROM could not be parsed"),
+            to: config
+                .smtp_to
+                .as_ref()
+                .map(|to| to.parse().expect("SMTP_TO could not be parsed")),
+        }
+    }
+}
+
+#[tonic::async_trait]
+impl Notifier for SmtpNotifier {
+    async fn notify(&self, notification: &Notification) -> Result<()> {
+        let to = notification
+            .owner
+            .parse::<Mailbox>()
+            .ok()
+            .or_else(|| self.to.clone())
+            .ok_or_else(|| Error::InternalError {
+                message: format!("no email recipient for owner {}", notification.owner),
+            })?;
+
+        let message = Message::builder()
+            .from(self.from.clone())
+            .to(to)
+            .message_id(Some(notification.message_id(self.from.email.domain())))
+            .subject(format!("Reminder: {}", notification.task_name))
+            .header(ContentType::TEXT_PLAIN)
+            .body(notification.text())
+            .map_err(|err| Error::InternalError {
+                message: format!("invalid reminder email: {}", err),
+            })?;
+
+        self.transport
+            .send(message)
+            .await
+            .map_err(|err| Error::InternalError {
+                message: format!("smtp delivery failed: {}", err),
+            })?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn message_id_is_stable_per_reminder() {
+        let notification = Notification {
+            reminder_id: Uuid::new_v4(),
+            task_id: Uuid::new_v4(),
+            task_name: "Call".to_string(),
+            story_id: Uuid::new_v4(),
+            owner: "someone@example.com".to_string(),
+            due_at: None,
+            fire_at: Utc::now(),
+        };
+        let message_id = notification.message_id("example.com");
+        assert_eq!(
+            message_id,
+            format!("<reminder-{}@example.com>", notification.reminder_id)
+        );
+        let retried = Notification {
+            fire_at: Utc::now(),
+            ..notification.clone()
+        };
+        assert_eq!(retried.message_id("example.com"), message_id);
+    }
+}
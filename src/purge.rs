@@ -0,0 +1,106 @@
+use chrono::{DateTime, Utc};
+use sqlx::postgres::PgPool;
+use sqlx::Error;
+use std::sync::Arc;
+use tokio::time::{self, Duration};
+
+/// Advisory lock key held while purging, so only one replica purges at a time.
+pub(crate) const PURGE_LOCK_KEY: i64 = 0x746f_646f_7350; // "todosP"
+
+/// Background job that hard-deletes soft-deleted rows past their retention period.
+pub struct Purge {
+    retention: chrono::Duration,
+    batch_size: i64,
+    interval: Duration,
+}
+
+impl Purge {
+    pub fn new(purge_after_days: i64, batch_size: i64, interval_secs: u64) -> Self {
+        Self {
+            retention: chrono::Duration::days(purge_after_days),
+            batch_size,
+            interval: Duration::from_secs(interval_secs),
+        }
+    }
+}
+
+impl Purge {
+    /// Hard-delete one batch of rows deleted before the cutoff. Returns `None`
+    /// when another replica holds the purge lock.
+    pub(crate) async fn purge_batch(
+        db: &PgPool,
+        cutoff: DateTime<Utc>,
+        batch_size: i64,
+    ) -> Result<Option<u64>, Error> {
+        log::debug!("Purge::purge_batch: {}, {}", cutoff, batch_size);
+
+        let mut tx = db.begin().await?;
+
+        let locked: bool = sqlx::query_scalar("SELECT pg_try_advisory_xact_lock($1)")
+            .bind(PURGE_LOCK_KEY)
+            .fetch_one(&mut *tx)
+            .await?;
+
+        if !locked {
+            return Ok(None);
+        }
+
+        let sql1 = r#"
+            DELETE FROM tasks WHERE id IN (
+                SELECT id FROM tasks
+                WHERE deleted_at < $1
+                LIMIT $2
+            )
+        "#;
+        let r1 = sqlx::query(sql1)
+            .bind(cutoff)
+            .bind(batch_size)
+            .execute(&mut *tx)
+            .await?;
+
+        // Stories still holding tasks wait for a later batch to remove them.
+        let sql2 = r#"
+            DELETE FROM stories WHERE id IN (
+                SELECT s.id FROM stories s
+                WHERE s.deleted_at < $1
+                AND NOT EXISTS (SELECT 1 FROM tasks t WHERE t.story_id = s.id)
+                LIMIT $2
+            )
+        "#;
+        let r2 = sqlx::query(sql2)
+            .bind(cutoff)
+            .bind(batch_size)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+
+        Ok(Some(r1.rows_affected() + r2.rows_affected()))
+    }
+
+    /// Periodically purge expired rows in bounded batches.
+    pub async fn run(self, db: Arc<PgPool>) {
+        log::info!("Starting purge job");
+        let db = db.as_ref();
+        loop {
+            time::sleep(self.interval).await;
+            let cutoff = Utc::now() - self.retention;
+            loop {
+                match Purge::purge_batch(db, cutoff, self.batch_size).await {
+                    Ok(Some(purged)) if purged > 0 => {
+                        log::info!("Purged {} soft-deleted rows", purged);
+                    }
+                    Ok(Some(_)) => break,
+                    Ok(None) => {
+                        log::debug!("Purge lock held by another replica");
+                        break;
+                    }
+                    Err(err) => {
+                        log::error!("Purge failed: {}", err.to_string());
+                        break;
+                    }
+                }
+            }
+        }
+    }
+}
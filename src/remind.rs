@@ -0,0 +1,136 @@
+use crate::notify::{Notification, Notifier};
+
+use sqlx::postgres::PgPool;
+use sqlx::{Error, Row};
+use std::sync::Arc;
+use tokio::time::{self, Duration};
+
+/// Deliveries attempted per reminder before it is given up on.
+const MAX_ATTEMPTS: i32 = 5;
+
+/// Delay before retrying a failed delivery, doubled after each further failure.
+const RETRY_BACKOFF_SECS: i64 = 60;
+
+/// Background job that delivers due reminders, at least once each.
+pub struct Remind {
+    notifier: Arc<dyn Notifier>,
+    batch_size: i64,
+    interval: Duration,
+}
+
+impl Remind {
+    pub fn new(notifier: Arc<dyn Notifier>, batch_size: i64, interval_secs: u64) -> Self {
+        Self {
+            notifier,
+            batch_size,
+            interval: Duration::from_secs(interval_secs),
+        }
+    }
+}
+
+impl Remind {
+    /// Claim and deliver one due reminder. The reminder row stays locked until
+    /// it is marked sent, and other replicas skip locked rows, so replicas
+    /// never deliver a reminder concurrently. Delivery is at least once: if the
+    /// job dies or the commit fails after the notifier succeeds, the reminder
+    /// is delivered again. Every delivery of a reminder carries its id, as the
+    /// webhook `Idempotency-Key` and in the email `Message-ID`, so receivers can
+    /// drop repeats. A failed delivery is retried after a backoff. Returns
+    /// `None` when nothing is due, otherwise whether delivery succeeded.
+    pub(crate) async fn deliver_next(
+        db: &PgPool,
+        notifier: &dyn Notifier,
+    ) -> Result<Option<bool>, Error> {
+        let mut tx = db.begin().await?;
+
+        let sql = r#"
+            SELECT r.id, r.task_id, t.name, t.story_id, s.owner, t.due_at,
+                COALESCE(r.remind_at, t.due_at - r.offset_secs * interval '1 second') AS fire_at
+            FROM reminders r
+            JOIN tasks t ON t.id = r.task_id AND t.deleted_at IS NULL
+            JOIN stories s ON s.id = t.story_id AND s.deleted_at IS NULL
+            WHERE r.sent_at IS NULL AND r.attempts < $1
+            AND (r.next_attempt_at IS NULL OR r.next_attempt_at <= now())
+            AND t.status NOT IN ('complete', 'cancelled')
+            AND COALESCE(r.remind_at, t.due_at - r.offset_secs * interval '1 second') <= now()
+            ORDER BY fire_at ASC
+            LIMIT 1
+            FOR UPDATE OF r SKIP LOCKED
+        "#;
+        let row = sqlx::query(sql)
+            .bind(MAX_ATTEMPTS)
+            .fetch_optional(&mut *tx)
+            .await?;
+
+        let row = match row {
+            Some(row) => row,
+            None => return Ok(None),
+        };
+        let notification = Notification {
+            reminder_id: row.try_get("id")?,
+            task_id: row.try_get("task_id")?,
+            task_name: row.try_get("name")?,
+            story_id: row.try_get("story_id")?,
+            owner: row.try_get("owner")?,
+            due_at: row.try_get("due_at")?,
+            fire_at: row.try_get("fire_at")?,
+        };
+
+        let result = notifier.notify(&notification).await;
+        if let Err(err) = &result {
+            log::warn!(
+                "Reminder {} delivery failed: {}",
+                notification.reminder_id,
+                err
+            );
+        }
+
+        let sql = r#"
+            UPDATE reminders SET
+                sent_at = CASE WHEN $2 THEN now() END,
+                attempts = attempts + 1,
+                last_error = $3,
+                next_attempt_at = CASE WHEN $2 THEN NULL
+                    ELSE now() + $4 * power(2, attempts) * interval '1 second' END
+            WHERE id = $1
+            RETURNING attempts
+        "#;
+        let attempts: i32 = sqlx::query_scalar(sql)
+            .bind(notification.reminder_id)
+            .bind(result.is_ok())
+            .bind(result.as_ref().err().map(|err| err.to_string()))
+            .bind(RETRY_BACKOFF_SECS)
+            .fetch_one(&mut *tx)
+            .await?;
+        if result.is_err() && attempts >= MAX_ATTEMPTS {
+            log::error!(
+                "Reminder {} given up after {} attempts",
+                notification.reminder_id,
+                attempts
+            );
+        }
+
+        tx.commit().await?;
+
+        Ok(Some(result.is_ok()))
+    }
+
+    /// Periodically deliver due reminders, up to a batch per interval.
+    pub async fn run(self, db: Arc<PgPool>) {
+        log::info!("Starting reminder job");
+        let db = db.as_ref();
+        loop {
+            time::sleep(self.interval).await;
+            for _ in 0..self.batch_size {
+                match Remind::deliver_next(db, self.notifier.as_ref()).await {
+                    Ok(Some(_)) => {}
+                    Ok(None) => break,
+                    Err(err) => {
+                        log::error!("Reminder delivery failed: {}", err.to_string());
+                        break;
+                    }
+                }
+            }
+        }
+    }
+}
@@ -1,16 +1,49 @@
+use crate::apikey::ApiKeys;
+use crate::auth::Caller;
+use crate::markdown;
+use crate::v1::entity::{LabelFilter, ReminderTrigger, StatusTarget, TaskFields};
+use crate::v1::proto::shared_story_service_server::SharedStoryService;
 use crate::v1::proto::todos_service_server::TodosService;
 use crate::v1::proto::*;
+use crate::v1::recurrence::Recurrence;
 use crate::v1::service::Service;
+use crate::validate::Validate;
 use crate::Error;
-use tonic::{Request, Response, Status};
+use chrono::{DateTime, TimeZone, Utc};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tonic::{Code, Request, Response, Status};
+use tonic_types::{ErrorDetails, StatusExt};
 
 /// Todos presentation layer (gRPC).
 pub struct Todos {
     service: Service,
+    api_keys: Option<Arc<ApiKeys>>,
 }
 
 impl Todos {
     /// Todos constructor
+    pub fn new(service: Service) -> Self {
+        Self {
+            service,
+            api_keys: None,
+        }
+    }
+
+    /// Keep this replica's API key cache in step with keys minted and revoked here.
+    pub fn with_api_keys(mut self, api_keys: Arc<ApiKeys>) -> Self {
+        self.api_keys = Some(api_keys);
+        self
+    }
+}
+
+/// Read-only presentation layer for share links (gRPC), served without auth.
+pub struct SharedStories {
+    service: Service,
+}
+
+impl SharedStories {
+    /// SharedStories constructor
     pub fn new(service: Service) -> Self {
         Self { service }
     }
@@ -23,10 +56,78 @@ impl From<Error> for Status {
             Error::InvalidArgument { message } => Status::invalid_argument(message),
             Error::InternalError { message } => Status::internal(message),
             Error::NotFoundError { message } => Status::not_found(message),
+            Error::FailedPrecondition { message } => Status::failed_precondition(message),
+            Error::PermissionDenied { message } => Status::permission_denied(message),
+            Error::Unauthenticated { message } => Status::unauthenticated(message),
+            Error::WipLimitExceeded {
+                scope,
+                limit,
+                count,
+            } => {
+                let message = format!(
+                    "{} limit of {} reached (current count {})",
+                    scope, limit, count
+                );
+                let metadata = HashMap::from([
+                    ("scope".to_string(), scope.clone()),
+                    ("limit".to_string(), limit.to_string()),
+                    ("count".to_string(), count.to_string()),
+                ]);
+                let mut details =
+                    ErrorDetails::with_precondition_failure_violation("WIP_LIMIT", scope, &message);
+                details.set_error_info("WIP_LIMIT_EXCEEDED", "todos.v1", metadata);
+                Status::with_error_details(Code::FailedPrecondition, message, details)
+            }
         }
     }
 }
 
+/// Map a timestamp into the protobuf well-known type
+fn timestamp(value: DateTime<Utc>) -> prost_types::Timestamp {
+    prost_types::Timestamp {
+        seconds: value.timestamp(),
+        nanos: value.timestamp_subsec_nanos() as i32,
+    }
+}
+
+/// Map an optional protobuf timestamp into a UTC date-time
+fn date_time(
+    value: Option<prost_types::Timestamp>,
+    field: &str,
+) -> Result<Option<DateTime<Utc>>, Error> {
+    value
+        .map(|ts| {
+            Utc.timestamp_opt(ts.seconds, ts.nanos.try_into().unwrap_or(u32::MAX))
+                .single()
+                .ok_or_else(|| Error::InvalidArgument {
+                    message: format!("{} is not a valid timestamp", field),
+                })
+        })
+        .transpose()
+}
+
+/// Parse a presentation recurrence rule; empty means the task does not repeat
+fn recurrence(value: &str) -> Result<Option<Recurrence>, Error> {
+    if value.trim().is_empty() {
+        return Ok(None);
+    }
+    value
+        .parse()
+        .map(Some)
+        .map_err(|message| Error::InvalidArgument { message })
+}
+
+/// Map presentation label ids and match mode into an entity filter
+fn label_filter(label_ids: &[String], label_match: LabelMatch) -> Result<LabelFilter, Error> {
+    Ok(LabelFilter {
+        label_ids: label_ids
+            .iter()
+            .map(|id| Validate::validate_uuid(id))
+            .collect::<Result<_, _>>()?,
+        match_all: label_match == LabelMatch::All,
+    })
+}
+
 /// Map entity story to presentation type
 impl From<crate::v1::entity::Story> for Story {
     fn from(entity: crate::v1::entity::Story) -> Self {
@@ -34,6 +135,123 @@ impl From<crate::v1::entity::Story> for Story {
             story_id: entity.story_id.to_string(),
             name: entity.name,
             owner: entity.owner,
+            created_at: Some(timestamp(entity.created_at)),
+            updated_at: Some(timestamp(entity.updated_at)),
+            task_count: entity.task_counts.total as u64,
+            completed_count: entity.task_counts.completed as u64,
+            overdue_count: entity.task_counts.overdue as u64,
+        }
+    }
+}
+
+/// Map entity owner summary into presentation type
+impl From<crate::v1::entity::OwnerSummary> for GetOwnerSummaryResponse {
+    fn from(entity: crate::v1::entity::OwnerSummary) -> Self {
+        Self {
+            owner: entity.owner,
+            story_count: entity.story_count as u64,
+            task_count: entity.task_counts.total as u64,
+            completed_count: entity.task_counts.completed as u64,
+            overdue_count: entity.task_counts.overdue as u64,
+        }
+    }
+}
+
+/// Map entity status to presentation type
+impl From<crate::v1::entity::Status> for TaskStatus {
+    fn from(entity: crate::v1::entity::Status) -> Self {
+        use crate::v1::entity::Status::*;
+        match entity {
+            Incomplete => TaskStatus::Todo,
+            InProgress => TaskStatus::InProgress,
+            Blocked => TaskStatus::Blocked,
+            Complete => TaskStatus::Done,
+            Cancelled => TaskStatus::Cancelled,
+        }
+    }
+}
+
+/// Map presentation status to entity type
+impl TryFrom<TaskStatus> for crate::v1::entity::Status {
+    type Error = Error;
+
+    fn try_from(status: TaskStatus) -> Result<Self, Self::Error> {
+        match status {
+            TaskStatus::Todo => Ok(Self::Incomplete),
+            TaskStatus::InProgress => Ok(Self::InProgress),
+            TaskStatus::Blocked => Ok(Self::Blocked),
+            TaskStatus::Done => Ok(Self::Complete),
+            TaskStatus::Cancelled => Ok(Self::Cancelled),
+            TaskStatus::Unspecified => Err(Error::InvalidArgument {
+                message: "unspecified task status".to_string(),
+            }),
+        }
+    }
+}
+
+/// Map entity role to presentation type
+impl From<crate::v1::entity::Role> for StoryRole {
+    fn from(entity: crate::v1::entity::Role) -> Self {
+        use crate::v1::entity::Role::*;
+        match entity {
+            Viewer => StoryRole::Viewer,
+            Editor => StoryRole::Editor,
+            Admin => StoryRole::Admin,
+            Owner => StoryRole::Owner,
+        }
+    }
+}
+
+/// Map presentation role to entity type
+impl TryFrom<StoryRole> for crate::v1::entity::Role {
+    type Error = Error;
+
+    fn try_from(role: StoryRole) -> Result<Self, Self::Error> {
+        match role {
+            StoryRole::Viewer => Ok(Self::Viewer),
+            StoryRole::Editor => Ok(Self::Editor),
+            StoryRole::Admin => Ok(Self::Admin),
+            StoryRole::Owner => Ok(Self::Owner),
+            StoryRole::Unspecified => Err(Error::InvalidArgument {
+                message: "unspecified story role".to_string(),
+            }),
+        }
+    }
+}
+
+/// Map entity priority to presentation type
+impl From<crate::v1::entity::Priority> for TaskPriority {
+    fn from(entity: crate::v1::entity::Priority) -> Self {
+        use crate::v1::entity::Priority::*;
+        match entity {
+            P0 => TaskPriority::P0,
+            P1 => TaskPriority::P1,
+            P2 => TaskPriority::P2,
+            P3 => TaskPriority::P3,
+        }
+    }
+}
+
+/// Map presentation priority to entity type; unspecified maps to `None`
+fn priority(priority: TaskPriority) -> Option<crate::v1::entity::Priority> {
+    use crate::v1::entity::Priority::*;
+    match priority {
+        TaskPriority::Unspecified => None,
+        TaskPriority::P0 => Some(P0),
+        TaskPriority::P1 => Some(P1),
+        TaskPriority::P2 => Some(P2),
+        TaskPriority::P3 => Some(P3),
+    }
+}
+
+/// Map presentation sort order to entity type
+impl From<TaskSortOrder> for crate::v1::entity::TaskSort {
+    fn from(order: TaskSortOrder) -> Self {
+        match order {
+            TaskSortOrder::Created => Self::Created,
+            TaskSortOrder::Priority => Self::Priority,
+            TaskSortOrder::Due => Self::Due,
+            TaskSortOrder::Unspecified | TaskSortOrder::Rank => Self::Rank,
         }
     }
 }
@@ -46,6 +264,193 @@ impl From<crate::v1::entity::Task> for Task {
             story_id: entity.story_id.to_string(),
             name: entity.name,
             complete: entity.status == crate::v1::entity::Status::Complete,
+            status: TaskStatus::from(entity.status).into(),
+            column: entity.column.unwrap_or_else(|| entity.status.to_string()),
+            due_at: entity.due_at.map(timestamp),
+            created_at: Some(timestamp(entity.created_at)),
+            updated_at: Some(timestamp(entity.updated_at)),
+            completed_at: entity.completed_at.map(timestamp),
+            priority: TaskPriority::from(entity.priority).into(),
+            label_ids: entity.label_ids.iter().map(|id| id.to_string()).collect(),
+            description: entity.description,
+            subtask_total: entity.subtask_total as u32,
+            subtask_done: entity.subtask_done as u32,
+            recurrence: entity.recurrence.map(|r| r.to_string()).unwrap_or_default(),
+        }
+    }
+}
+
+/// Map entity workflow column into presentation type
+impl From<crate::v1::entity::WorkflowColumn> for WorkflowColumn {
+    fn from(entity: crate::v1::entity::WorkflowColumn) -> Self {
+        Self {
+            name: entity.name,
+            status: TaskStatus::from(entity.status).into(),
+        }
+    }
+}
+
+/// Map presentation workflow column into entity type
+impl TryFrom<WorkflowColumn> for crate::v1::entity::WorkflowColumn {
+    type Error = Error;
+
+    fn try_from(column: WorkflowColumn) -> Result<Self, Self::Error> {
+        Ok(Self {
+            status: column.status().try_into()?,
+            name: column.name,
+        })
+    }
+}
+
+/// Map entity WIP limit into presentation type
+impl From<crate::v1::entity::WipLimit> for WipLimit {
+    fn from(entity: crate::v1::entity::WipLimit) -> Self {
+        Self {
+            column: entity.column.unwrap_or_default(),
+            max_tasks: entity.max_tasks as u32,
+        }
+    }
+}
+
+/// Map entity board column into presentation type
+impl From<crate::v1::entity::BoardColumn> for BoardColumn {
+    fn from(entity: crate::v1::entity::BoardColumn) -> Self {
+        Self {
+            name: entity.column.name,
+            status: TaskStatus::from(entity.column.status).into(),
+            tasks: entity.tasks.into_iter().map(|t| t.into()).collect(),
+        }
+    }
+}
+
+/// Map entity checklist item into presentation type
+impl From<crate::v1::entity::ChecklistItem> for ChecklistItem {
+    fn from(entity: crate::v1::entity::ChecklistItem) -> Self {
+        Self {
+            item_id: entity.item_id.to_string(),
+            task_id: entity.task_id.to_string(),
+            name: entity.name,
+            done: entity.done,
+            created_at: Some(timestamp(entity.created_at)),
+            updated_at: Some(timestamp(entity.updated_at)),
+        }
+    }
+}
+
+/// Map entity reminder into presentation type
+impl From<crate::v1::entity::Reminder> for Reminder {
+    fn from(entity: crate::v1::entity::Reminder) -> Self {
+        let (remind_at, before_due) = match entity.trigger {
+            ReminderTrigger::At(at) => (Some(timestamp(at)), None),
+            ReminderTrigger::BeforeDue(offset) => (
+                None,
+                Some(prost_types::Duration {
+                    seconds: offset.num_seconds(),
+                    nanos: 0,
+                }),
+            ),
+        };
+        Self {
+            reminder_id: entity.reminder_id.to_string(),
+            task_id: entity.task_id.to_string(),
+            remind_at,
+            before_due,
+            fire_at: entity.fire_at.map(timestamp),
+            created_at: Some(timestamp(entity.created_at)),
+            sent_at: entity.sent_at.map(timestamp),
+        }
+    }
+}
+
+/// Map a presentation reminder time or offset into an entity trigger
+fn reminder_trigger(
+    remind_at: Option<prost_types::Timestamp>,
+    before_due: Option<prost_types::Duration>,
+) -> Result<ReminderTrigger, Error> {
+    match (date_time(remind_at, "remind_at")?, before_due) {
+        (Some(at), None) => Ok(ReminderTrigger::At(at)),
+        (None, Some(offset)) => Ok(ReminderTrigger::BeforeDue(chrono::Duration::seconds(
+            offset.seconds,
+        ))),
+        _ => Err(Error::InvalidArgument {
+            message: "exactly one of remind_at or before_due is required".to_string(),
+        }),
+    }
+}
+
+/// Map entity label into presentation type
+impl From<crate::v1::entity::Label> for Label {
+    fn from(entity: crate::v1::entity::Label) -> Self {
+        Self {
+            label_id: entity.label_id.to_string(),
+            owner: entity.owner,
+            name: entity.name,
+            color: entity.color,
+            created_at: Some(timestamp(entity.created_at)),
+        }
+    }
+}
+
+/// Map entity story member into presentation type
+impl From<crate::v1::entity::StoryMember> for StoryMember {
+    fn from(entity: crate::v1::entity::StoryMember) -> Self {
+        Self {
+            story_id: entity.story_id.to_string(),
+            member: entity.member,
+            role: StoryRole::from(entity.role).into(),
+            created_at: Some(timestamp(entity.created_at)),
+            updated_at: Some(timestamp(entity.updated_at)),
+        }
+    }
+}
+
+/// Map entity share link into presentation type
+impl From<crate::v1::entity::ShareLink> for ShareLink {
+    fn from(entity: crate::v1::entity::ShareLink) -> Self {
+        Self {
+            link_id: entity.link_id.to_string(),
+            story_id: entity.story_id.to_string(),
+            created_by: entity.created_by,
+            created_at: Some(timestamp(entity.created_at)),
+            expires_at: entity.expires_at.map(timestamp),
+            revoked_at: entity.revoked_at.map(timestamp),
+            access_count: entity.access_count,
+            last_accessed_at: entity.last_accessed_at.map(timestamp),
+        }
+    }
+}
+
+/// Map entity API key into presentation type
+impl From<crate::v1::entity::ApiKey> for ApiKey {
+    fn from(entity: crate::v1::entity::ApiKey) -> Self {
+        Self {
+            key_id: entity.key_id.to_string(),
+            owner: entity.owner,
+            name: entity.name,
+            created_at: Some(timestamp(entity.created_at)),
+            expires_at: entity.expires_at.map(timestamp),
+            revoked_at: entity.revoked_at.map(timestamp),
+            last_used_at: entity.last_used_at.map(timestamp),
+        }
+    }
+}
+
+/// Map deleted entity story into presentation type
+impl From<crate::v1::entity::Trashed<crate::v1::entity::Story>> for TrashedStory {
+    fn from(entity: crate::v1::entity::Trashed<crate::v1::entity::Story>) -> Self {
+        Self {
+            story: Some(entity.item.into()),
+            deleted_at: Some(timestamp(entity.deleted_at)),
+        }
+    }
+}
+
+/// Map deleted entity task into presentation type
+impl From<crate::v1::entity::Trashed<crate::v1::entity::Task>> for TrashedTask {
+    fn from(entity: crate::v1::entity::Trashed<crate::v1::entity::Task>) -> Self {
+        Self {
+            task: Some(entity.item.into()),
+            deleted_at: Some(timestamp(entity.deleted_at)),
         }
     }
 }
@@ -59,10 +464,10 @@ impl TodosService for Todos {
     ) -> Result<Response<CreateStoryResponse>, Status> {
         log::info!("Create story request from {:?}", request.remote_addr());
 
-        let request = request.get_ref();
+        let caller = Caller::from_request(&request)?;
         let entity = self
             .service
-            .create_story(&request.name, &request.owner)
+            .create_story(caller, &request.get_ref().name)
             .await?;
 
         Ok(Response::new(CreateStoryResponse {
@@ -70,6 +475,24 @@ impl TodosService for Todos {
         }))
     }
 
+    /// Get a single story
+    async fn get_story(
+        &self,
+        request: Request<GetStoryRequest>,
+    ) -> Result<Response<GetStoryResponse>, Status> {
+        log::info!("Get story request from {:?}", request.remote_addr());
+
+        let caller = Caller::from_request(&request)?;
+        let entity = self
+            .service
+            .get_story(caller, &request.get_ref().story_id)
+            .await?;
+
+        Ok(Response::new(GetStoryResponse {
+            story: Some(entity.into()),
+        }))
+    }
+
     /// Get owner stories
     async fn get_stories(
         &self,
@@ -77,15 +500,54 @@ impl TodosService for Todos {
     ) -> Result<Response<GetStoriesResponse>, Status> {
         log::info!("Get stories request from {:?}", request.remote_addr());
 
-        let stories = self
+        let caller = Caller::from_request(&request)?;
+        let request = request.get_ref();
+        let page = self
             .service
-            .get_stories(&request.get_ref().owner)
-            .await?
-            .into_iter()
-            .map(|s| s.into())
-            .collect();
+            .get_stories(caller, request.page_size, &request.page_token)
+            .await?;
 
-        Ok(Response::new(GetStoriesResponse { stories }))
+        let next_page_token = page.next_page_token();
+        let stories = page.items.into_iter().map(|s| s.into()).collect();
+
+        Ok(Response::new(GetStoriesResponse {
+            stories,
+            next_page_token,
+        }))
+    }
+
+    /// Get an owner's story and task totals
+    async fn get_owner_summary(
+        &self,
+        request: Request<GetOwnerSummaryRequest>,
+    ) -> Result<Response<GetOwnerSummaryResponse>, Status> {
+        log::info!("Get owner summary request from {:?}", request.remote_addr());
+
+        let caller = Caller::from_request(&request)?;
+        let summary = self.service.get_owner_summary(caller).await?;
+
+        Ok(Response::new(summary.into()))
+    }
+
+    /// Update a story
+    async fn update_story(
+        &self,
+        request: Request<UpdateStoryRequest>,
+    ) -> Result<Response<UpdateStoryResponse>, Status> {
+        log::info!("Update story request from {:?}", request.remote_addr());
+
+        let caller = Caller::from_request(&request)?.clone();
+        let request = request.into_inner();
+        let story = request.story.unwrap_or_default();
+        let update_mask = request.update_mask.unwrap_or_default();
+        let entity = self
+            .service
+            .update_story(&caller, &story.story_id, &story.name, &update_mask.paths)
+            .await?;
+
+        Ok(Response::new(UpdateStoryResponse {
+            story: Some(entity.into()),
+        }))
     }
 
     /// Create a new task
@@ -95,10 +557,18 @@ impl TodosService for Todos {
     ) -> Result<Response<CreateTaskResponse>, Status> {
         log::info!("Create task request from {:?}", request.remote_addr());
 
-        let request = request.get_ref();
+        let caller = Caller::from_request(&request)?.clone();
+        let request = request.into_inner();
+        let fields = TaskFields {
+            priority: priority(request.priority()),
+            due_at: date_time(request.due_at, "due_at")?,
+            recurrence: recurrence(&request.recurrence)?,
+            name: request.name,
+            description: request.description,
+        };
         let entity = self
             .service
-            .create_task(&request.story_id, &request.name)
+            .create_task(&caller, &request.story_id, fields)
             .await?;
 
         Ok(Response::new(CreateTaskResponse {
@@ -106,6 +576,42 @@ impl TodosService for Todos {
         }))
     }
 
+    /// Get a single task
+    async fn get_task(
+        &self,
+        request: Request<GetTaskRequest>,
+    ) -> Result<Response<GetTaskResponse>, Status> {
+        log::info!("Get task request from {:?}", request.remote_addr());
+
+        let caller = Caller::from_request(&request)?;
+        let request = request.get_ref();
+        let entity = self.service.get_task(caller, &request.task_id).await?;
+        let dependencies = self
+            .service
+            .get_task_dependencies(caller, &request.task_id)
+            .await?;
+        let description_html = if request.render_description {
+            markdown::render(&entity.description)
+        } else {
+            String::new()
+        };
+
+        Ok(Response::new(GetTaskResponse {
+            task: Some(entity.into()),
+            description_html,
+            blocked_by: dependencies
+                .blocked_by
+                .into_iter()
+                .map(|t| t.into())
+                .collect(),
+            blocking: dependencies
+                .blocking
+                .into_iter()
+                .map(|t| t.into())
+                .collect(),
+        }))
+    }
+
     /// Get all tasks for a story
     async fn get_tasks(
         &self,
@@ -113,15 +619,55 @@ impl TodosService for Todos {
     ) -> Result<Response<GetTasksResponse>, Status> {
         log::info!("Get tasks request from {:?}", request.remote_addr());
 
-        let tasks = self
+        let caller = Caller::from_request(&request)?;
+        let request = request.get_ref();
+        let page = self
             .service
-            .get_tasks(&request.get_ref().story_id)
-            .await?
-            .into_iter()
-            .map(|t| t.into())
-            .collect();
+            .get_tasks(
+                caller,
+                &request.story_id,
+                request.sort_order().into(),
+                label_filter(&request.label_ids, request.label_match())?,
+                request.page_size,
+                &request.page_token,
+            )
+            .await?;
+
+        let next_page_token = page.next_page_token();
+        let tasks = page.items.into_iter().map(|t| t.into()).collect();
+
+        Ok(Response::new(GetTasksResponse {
+            tasks,
+            next_page_token,
+        }))
+    }
+
+    /// Update a task
+    async fn update_task(
+        &self,
+        request: Request<UpdateTaskRequest>,
+    ) -> Result<Response<UpdateTaskResponse>, Status> {
+        log::info!("Update task request from {:?}", request.remote_addr());
+
+        let caller = Caller::from_request(&request)?.clone();
+        let request = request.into_inner();
+        let task = request.task.unwrap_or_default();
+        let update_mask = request.update_mask.unwrap_or_default();
+        let fields = TaskFields {
+            priority: priority(task.priority()),
+            due_at: date_time(task.due_at, "due_at")?,
+            recurrence: recurrence(&task.recurrence)?,
+            name: task.name,
+            description: task.description,
+        };
+        let entity = self
+            .service
+            .update_task(&caller, &task.task_id, fields, &update_mask.paths)
+            .await?;
 
-        Ok(Response::new(GetTasksResponse { tasks }))
+        Ok(Response::new(UpdateTaskResponse {
+            task: Some(entity.into()),
+        }))
     }
 
     /// Complete a task
@@ -131,13 +677,152 @@ impl TodosService for Todos {
     ) -> Result<Response<CompleteTaskResponse>, Status> {
         log::info!("Complete task request from {:?}", request.remote_addr());
 
+        let caller = Caller::from_request(&request)?;
         self.service
-            .complete_task(&request.get_ref().task_id)
+            .complete_task(caller, &request.get_ref().task_id)
             .await?;
 
         Ok(Response::new(CompleteTaskResponse {}))
     }
 
+    /// Set the status of a task
+    async fn set_task_status(
+        &self,
+        request: Request<SetTaskStatusRequest>,
+    ) -> Result<Response<SetTaskStatusResponse>, Status> {
+        log::info!("Set task status request from {:?}", request.remote_addr());
+
+        let caller = Caller::from_request(&request)?.clone();
+        let request = request.into_inner();
+        let target = if request.column.is_empty() {
+            StatusTarget::Status(request.status().try_into()?)
+        } else {
+            StatusTarget::Column(request.column)
+        };
+        let entity = self
+            .service
+            .set_task_status(&caller, &request.task_id, target)
+            .await?;
+
+        Ok(Response::new(SetTaskStatusResponse {
+            task: Some(entity.into()),
+        }))
+    }
+
+    /// Move a task within its story's manual order
+    async fn move_task(
+        &self,
+        request: Request<MoveTaskRequest>,
+    ) -> Result<Response<MoveTaskResponse>, Status> {
+        log::info!("Move task request from {:?}", request.remote_addr());
+
+        let caller = Caller::from_request(&request)?;
+        let request = request.get_ref();
+        let entity = self
+            .service
+            .move_task(
+                caller,
+                &request.task_id,
+                &request.before_task_id,
+                &request.after_task_id,
+            )
+            .await?;
+
+        Ok(Response::new(MoveTaskResponse {
+            task: Some(entity.into()),
+        }))
+    }
+
+    /// Move a task to another story
+    async fn move_task_to_story(
+        &self,
+        request: Request<MoveTaskToStoryRequest>,
+    ) -> Result<Response<MoveTaskToStoryResponse>, Status> {
+        log::info!(
+            "Move task to story request from {:?}",
+            request.remote_addr()
+        );
+
+        let caller = Caller::from_request(&request)?;
+        let request = request.get_ref();
+        let entity = self
+            .service
+            .move_task_to_story(caller, &request.task_id, &request.story_id)
+            .await?;
+
+        Ok(Response::new(MoveTaskToStoryResponse {
+            task: Some(entity.into()),
+        }))
+    }
+
+    /// Get the board for a story
+    async fn get_board(
+        &self,
+        request: Request<GetBoardRequest>,
+    ) -> Result<Response<GetBoardResponse>, Status> {
+        log::info!("Get board request from {:?}", request.remote_addr());
+
+        let caller = Caller::from_request(&request)?;
+        let columns = self
+            .service
+            .get_board(caller, &request.get_ref().story_id)
+            .await?
+            .into_iter()
+            .map(|c| c.into())
+            .collect();
+
+        Ok(Response::new(GetBoardResponse { columns }))
+    }
+
+    /// Replace the workflow for a story
+    async fn set_workflow(
+        &self,
+        request: Request<SetWorkflowRequest>,
+    ) -> Result<Response<SetWorkflowResponse>, Status> {
+        log::info!("Set workflow request from {:?}", request.remote_addr());
+
+        let caller = Caller::from_request(&request)?.clone();
+        let request = request.into_inner();
+        let columns = request
+            .columns
+            .into_iter()
+            .map(|c| c.try_into())
+            .collect::<Result<Vec<_>, Error>>()?;
+        let workflow = self
+            .service
+            .set_workflow(&caller, &request.story_id, columns)
+            .await?;
+
+        Ok(Response::new(SetWorkflowResponse {
+            columns: workflow.columns.into_iter().map(|c| c.into()).collect(),
+        }))
+    }
+
+    /// Set or remove a WIP limit for a story
+    async fn set_wip_limit(
+        &self,
+        request: Request<SetWipLimitRequest>,
+    ) -> Result<Response<SetWipLimitResponse>, Status> {
+        log::info!("Set WIP limit request from {:?}", request.remote_addr());
+
+        let caller = Caller::from_request(&request)?;
+        let request = request.get_ref();
+        let limits = self
+            .service
+            .set_wip_limit(
+                caller,
+                &request.story_id,
+                &request.column,
+                request.max_tasks,
+            )
+            .await?
+            .into_iter()
+            .map(|l| l.into())
+            .collect();
+
+        Ok(Response::new(SetWipLimitResponse { limits }))
+    }
+
     /// Delete a story
     async fn delete_story(
         &self,
@@ -145,8 +830,9 @@ impl TodosService for Todos {
     ) -> Result<Response<DeleteStoryResponse>, Status> {
         log::info!("Delete story request from {:?}", request.remote_addr());
 
+        let caller = Caller::from_request(&request)?;
         self.service
-            .delete_story(&request.get_ref().story_id)
+            .delete_story(caller, &request.get_ref().story_id)
             .await?;
 
         Ok(Response::new(DeleteStoryResponse {}))
@@ -159,8 +845,601 @@ impl TodosService for Todos {
     ) -> Result<Response<DeleteTaskResponse>, Status> {
         log::info!("Delete task request from {:?}", request.remote_addr());
 
-        self.service.delete_task(&request.get_ref().task_id).await?;
+        let caller = Caller::from_request(&request)?;
+        self.service
+            .delete_task(caller, &request.get_ref().task_id)
+            .await?;
 
         Ok(Response::new(DeleteTaskResponse {}))
     }
+
+    /// Restore a deleted story
+    async fn restore_story(
+        &self,
+        request: Request<RestoreStoryRequest>,
+    ) -> Result<Response<RestoreStoryResponse>, Status> {
+        log::info!("Restore story request from {:?}", request.remote_addr());
+
+        let caller = Caller::from_request(&request)?;
+        let entity = self
+            .service
+            .restore_story(caller, &request.get_ref().story_id)
+            .await?;
+
+        Ok(Response::new(RestoreStoryResponse {
+            story: Some(entity.into()),
+        }))
+    }
+
+    /// Restore a deleted task
+    async fn restore_task(
+        &self,
+        request: Request<RestoreTaskRequest>,
+    ) -> Result<Response<RestoreTaskResponse>, Status> {
+        log::info!("Restore task request from {:?}", request.remote_addr());
+
+        let caller = Caller::from_request(&request)?;
+        let entity = self
+            .service
+            .restore_task(caller, &request.get_ref().task_id)
+            .await?;
+
+        Ok(Response::new(RestoreTaskResponse {
+            task: Some(entity.into()),
+        }))
+    }
+
+    /// List deleted stories and tasks
+    async fn list_trash(
+        &self,
+        request: Request<ListTrashRequest>,
+    ) -> Result<Response<ListTrashResponse>, Status> {
+        log::info!("List trash request from {:?}", request.remote_addr());
+
+        let caller = Caller::from_request(&request)?;
+        let trash = self.service.list_trash(caller).await?;
+
+        Ok(Response::new(ListTrashResponse {
+            stories: trash.stories.into_iter().map(|s| s.into()).collect(),
+            tasks: trash.tasks.into_iter().map(|t| t.into()).collect(),
+        }))
+    }
+
+    /// Get open tasks that are due in a window or overdue
+    async fn get_due_tasks(
+        &self,
+        request: Request<GetDueTasksRequest>,
+    ) -> Result<Response<GetDueTasksResponse>, Status> {
+        log::info!("Get due tasks request from {:?}", request.remote_addr());
+
+        let caller = Caller::from_request(&request)?.clone();
+        let request = request.into_inner();
+        let page = self
+            .service
+            .get_due_tasks(
+                &caller,
+                date_time(request.due_after, "due_after")?,
+                date_time(request.due_before, "due_before")?,
+                request.include_overdue,
+                request.page_size,
+                &request.page_token,
+            )
+            .await?;
+
+        let next_page_token = page.next_page_token();
+        let tasks = page.items.into_iter().map(|t| t.into()).collect();
+
+        Ok(Response::new(GetDueTasksResponse {
+            tasks,
+            next_page_token,
+        }))
+    }
+
+    /// Create a label
+    async fn create_label(
+        &self,
+        request: Request<CreateLabelRequest>,
+    ) -> Result<Response<CreateLabelResponse>, Status> {
+        log::info!("Create label request from {:?}", request.remote_addr());
+
+        let caller = Caller::from_request(&request)?;
+        let request = request.get_ref();
+        let entity = self
+            .service
+            .create_label(caller, &request.name, &request.color)
+            .await?;
+
+        Ok(Response::new(CreateLabelResponse {
+            label: Some(entity.into()),
+        }))
+    }
+
+    /// Get owner labels
+    async fn get_labels(
+        &self,
+        request: Request<GetLabelsRequest>,
+    ) -> Result<Response<GetLabelsResponse>, Status> {
+        log::info!("Get labels request from {:?}", request.remote_addr());
+
+        let caller = Caller::from_request(&request)?;
+        let labels = self
+            .service
+            .get_labels(caller, &request.get_ref().story_id)
+            .await?
+            .into_iter()
+            .map(|l| l.into())
+            .collect();
+
+        Ok(Response::new(GetLabelsResponse { labels }))
+    }
+
+    /// Delete a label
+    async fn delete_label(
+        &self,
+        request: Request<DeleteLabelRequest>,
+    ) -> Result<Response<DeleteLabelResponse>, Status> {
+        log::info!("Delete label request from {:?}", request.remote_addr());
+
+        let caller = Caller::from_request(&request)?;
+        self.service
+            .delete_label(caller, &request.get_ref().label_id)
+            .await?;
+
+        Ok(Response::new(DeleteLabelResponse {}))
+    }
+
+    /// Attach a label to a task
+    async fn attach_label(
+        &self,
+        request: Request<AttachLabelRequest>,
+    ) -> Result<Response<AttachLabelResponse>, Status> {
+        log::info!("Attach label request from {:?}", request.remote_addr());
+
+        let caller = Caller::from_request(&request)?;
+        let request = request.get_ref();
+        let entity = self
+            .service
+            .attach_label(caller, &request.task_id, &request.label_id)
+            .await?;
+
+        Ok(Response::new(AttachLabelResponse {
+            task: Some(entity.into()),
+        }))
+    }
+
+    /// Detach a label from a task
+    async fn detach_label(
+        &self,
+        request: Request<DetachLabelRequest>,
+    ) -> Result<Response<DetachLabelResponse>, Status> {
+        log::info!("Detach label request from {:?}", request.remote_addr());
+
+        let caller = Caller::from_request(&request)?;
+        let request = request.get_ref();
+        let entity = self
+            .service
+            .detach_label(caller, &request.task_id, &request.label_id)
+            .await?;
+
+        Ok(Response::new(DetachLabelResponse {
+            task: Some(entity.into()),
+        }))
+    }
+
+    /// Get owner tasks by label
+    async fn get_labeled_tasks(
+        &self,
+        request: Request<GetLabeledTasksRequest>,
+    ) -> Result<Response<GetLabeledTasksResponse>, Status> {
+        log::info!("Get labeled tasks request from {:?}", request.remote_addr());
+
+        let caller = Caller::from_request(&request)?;
+        let request = request.get_ref();
+        let page = self
+            .service
+            .get_labeled_tasks(
+                caller,
+                label_filter(&request.label_ids, request.label_match())?,
+                request.page_size,
+                &request.page_token,
+            )
+            .await?;
+
+        let next_page_token = page.next_page_token();
+        let tasks = page.items.into_iter().map(|t| t.into()).collect();
+
+        Ok(Response::new(GetLabeledTasksResponse {
+            tasks,
+            next_page_token,
+        }))
+    }
+
+    /// Add a checklist item
+    async fn add_checklist_item(
+        &self,
+        request: Request<AddChecklistItemRequest>,
+    ) -> Result<Response<AddChecklistItemResponse>, Status> {
+        log::info!(
+            "Add checklist item request from {:?}",
+            request.remote_addr()
+        );
+
+        let caller = Caller::from_request(&request)?;
+        let request = request.get_ref();
+        let entity = self
+            .service
+            .add_checklist_item(caller, &request.task_id, &request.name)
+            .await?;
+
+        Ok(Response::new(AddChecklistItemResponse {
+            item: Some(entity.into()),
+        }))
+    }
+
+    /// Get a task checklist
+    async fn get_checklist(
+        &self,
+        request: Request<GetChecklistRequest>,
+    ) -> Result<Response<GetChecklistResponse>, Status> {
+        log::info!("Get checklist request from {:?}", request.remote_addr());
+
+        let caller = Caller::from_request(&request)?;
+        let items = self
+            .service
+            .get_checklist(caller, &request.get_ref().task_id)
+            .await?
+            .into_iter()
+            .map(|i| i.into())
+            .collect();
+
+        Ok(Response::new(GetChecklistResponse { items }))
+    }
+
+    /// Mark a checklist item done or not done
+    async fn toggle_checklist_item(
+        &self,
+        request: Request<ToggleChecklistItemRequest>,
+    ) -> Result<Response<ToggleChecklistItemResponse>, Status> {
+        log::info!(
+            "Toggle checklist item request from {:?}",
+            request.remote_addr()
+        );
+
+        let caller = Caller::from_request(&request)?;
+        let request = request.get_ref();
+        let entity = self
+            .service
+            .toggle_checklist_item(caller, &request.item_id, request.done)
+            .await?;
+
+        Ok(Response::new(ToggleChecklistItemResponse {
+            item: Some(entity.into()),
+        }))
+    }
+
+    /// Move a checklist item
+    async fn move_checklist_item(
+        &self,
+        request: Request<MoveChecklistItemRequest>,
+    ) -> Result<Response<MoveChecklistItemResponse>, Status> {
+        log::info!(
+            "Move checklist item request from {:?}",
+            request.remote_addr()
+        );
+
+        let caller = Caller::from_request(&request)?;
+        let request = request.get_ref();
+        let entity = self
+            .service
+            .move_checklist_item(
+                caller,
+                &request.item_id,
+                &request.before_item_id,
+                &request.after_item_id,
+            )
+            .await?;
+
+        Ok(Response::new(MoveChecklistItemResponse {
+            item: Some(entity.into()),
+        }))
+    }
+
+    /// Delete a checklist item
+    async fn delete_checklist_item(
+        &self,
+        request: Request<DeleteChecklistItemRequest>,
+    ) -> Result<Response<DeleteChecklistItemResponse>, Status> {
+        log::info!(
+            "Delete checklist item request from {:?}",
+            request.remote_addr()
+        );
+
+        let caller = Caller::from_request(&request)?;
+        self.service
+            .delete_checklist_item(caller, &request.get_ref().item_id)
+            .await?;
+
+        Ok(Response::new(DeleteChecklistItemResponse {}))
+    }
+
+    /// Add a task dependency
+    async fn add_dependency(
+        &self,
+        request: Request<AddDependencyRequest>,
+    ) -> Result<Response<AddDependencyResponse>, Status> {
+        log::info!("Add dependency request from {:?}", request.remote_addr());
+
+        let caller = Caller::from_request(&request)?;
+        let request = request.get_ref();
+        self.service
+            .add_dependency(caller, &request.task_id, &request.blocker_task_id)
+            .await?;
+
+        Ok(Response::new(AddDependencyResponse {}))
+    }
+
+    /// Remove a task dependency
+    async fn remove_dependency(
+        &self,
+        request: Request<RemoveDependencyRequest>,
+    ) -> Result<Response<RemoveDependencyResponse>, Status> {
+        log::info!("Remove dependency request from {:?}", request.remote_addr());
+
+        let caller = Caller::from_request(&request)?;
+        let request = request.get_ref();
+        self.service
+            .remove_dependency(caller, &request.task_id, &request.blocker_task_id)
+            .await?;
+
+        Ok(Response::new(RemoveDependencyResponse {}))
+    }
+
+    /// Add a reminder to a task
+    async fn add_reminder(
+        &self,
+        request: Request<AddReminderRequest>,
+    ) -> Result<Response<AddReminderResponse>, Status> {
+        log::info!("Add reminder request from {:?}", request.remote_addr());
+
+        let caller = Caller::from_request(&request)?.clone();
+        let request = request.into_inner();
+        let trigger = reminder_trigger(request.remind_at, request.before_due)?;
+        let entity = self
+            .service
+            .add_reminder(&caller, &request.task_id, trigger)
+            .await?;
+
+        Ok(Response::new(AddReminderResponse {
+            reminder: Some(entity.into()),
+        }))
+    }
+
+    /// Get the reminders of a task
+    async fn get_reminders(
+        &self,
+        request: Request<GetRemindersRequest>,
+    ) -> Result<Response<GetRemindersResponse>, Status> {
+        log::info!("Get reminders request from {:?}", request.remote_addr());
+
+        let caller = Caller::from_request(&request)?;
+        let reminders = self
+            .service
+            .get_reminders(caller, &request.get_ref().task_id)
+            .await?
+            .into_iter()
+            .map(|r| r.into())
+            .collect();
+
+        Ok(Response::new(GetRemindersResponse { reminders }))
+    }
+
+    /// Delete a reminder
+    async fn delete_reminder(
+        &self,
+        request: Request<DeleteReminderRequest>,
+    ) -> Result<Response<DeleteReminderResponse>, Status> {
+        log::info!("Delete reminder request from {:?}", request.remote_addr());
+
+        let caller = Caller::from_request(&request)?;
+        self.service
+            .delete_reminder(caller, &request.get_ref().reminder_id)
+            .await?;
+
+        Ok(Response::new(DeleteReminderResponse {}))
+    }
+
+    /// Share a story with a member, or change their role
+    async fn share_story(
+        &self,
+        request: Request<ShareStoryRequest>,
+    ) -> Result<Response<ShareStoryResponse>, Status> {
+        log::info!("Share story request from {:?}", request.remote_addr());
+
+        let caller = Caller::from_request(&request)?;
+        let request = request.get_ref();
+        let entity = self
+            .service
+            .share_story(
+                caller,
+                &request.story_id,
+                &request.member,
+                request.role().try_into()?,
+            )
+            .await?;
+
+        Ok(Response::new(ShareStoryResponse {
+            story_member: Some(entity.into()),
+        }))
+    }
+
+    /// Stop sharing a story with a member
+    async fn unshare_story(
+        &self,
+        request: Request<UnshareStoryRequest>,
+    ) -> Result<Response<UnshareStoryResponse>, Status> {
+        log::info!("Unshare story request from {:?}", request.remote_addr());
+
+        let caller = Caller::from_request(&request)?;
+        let request = request.get_ref();
+        self.service
+            .unshare_story(caller, &request.story_id, &request.member)
+            .await?;
+
+        Ok(Response::new(UnshareStoryResponse {}))
+    }
+
+    /// List the members a story is shared with
+    async fn list_story_members(
+        &self,
+        request: Request<ListStoryMembersRequest>,
+    ) -> Result<Response<ListStoryMembersResponse>, Status> {
+        log::info!(
+            "List story members request from {:?}",
+            request.remote_addr()
+        );
+
+        let caller = Caller::from_request(&request)?;
+        let members = self
+            .service
+            .list_story_members(caller, &request.get_ref().story_id)
+            .await?
+            .into_iter()
+            .map(|m| m.into())
+            .collect();
+
+        Ok(Response::new(ListStoryMembersResponse { members }))
+    }
+
+    /// Mint a read-only share link for a story
+    async fn create_share_link(
+        &self,
+        request: Request<CreateShareLinkRequest>,
+    ) -> Result<Response<CreateShareLinkResponse>, Status> {
+        log::info!("Create share link request from {:?}", request.remote_addr());
+
+        let caller = Caller::from_request(&request)?.clone();
+        let request = request.into_inner();
+        let expires_at = date_time(request.expires_at, "expires_at")?;
+        let (entity, token) = self
+            .service
+            .create_share_link(&caller, &request.story_id, expires_at)
+            .await?;
+
+        Ok(Response::new(CreateShareLinkResponse {
+            share_link: Some(entity.into()),
+            token,
+        }))
+    }
+
+    /// List the share links of a story
+    async fn list_share_links(
+        &self,
+        request: Request<ListShareLinksRequest>,
+    ) -> Result<Response<ListShareLinksResponse>, Status> {
+        log::info!("List share links request from {:?}", request.remote_addr());
+
+        let caller = Caller::from_request(&request)?;
+        let share_links = self
+            .service
+            .list_share_links(caller, &request.get_ref().story_id)
+            .await?
+            .into_iter()
+            .map(|l| l.into())
+            .collect();
+
+        Ok(Response::new(ListShareLinksResponse { share_links }))
+    }
+
+    /// Revoke a share link
+    async fn revoke_share_link(
+        &self,
+        request: Request<RevokeShareLinkRequest>,
+    ) -> Result<Response<RevokeShareLinkResponse>, Status> {
+        log::info!("Revoke share link request from {:?}", request.remote_addr());
+
+        let caller = Caller::from_request(&request)?;
+        let entity = self
+            .service
+            .revoke_share_link(caller, &request.get_ref().link_id)
+            .await?;
+
+        Ok(Response::new(RevokeShareLinkResponse {
+            share_link: Some(entity.into()),
+        }))
+    }
+
+    /// Mint an API key (admins only)
+    async fn create_api_key(
+        &self,
+        request: Request<CreateApiKeyRequest>,
+    ) -> Result<Response<CreateApiKeyResponse>, Status> {
+        log::info!("Create API key request from {:?}", request.remote_addr());
+
+        let caller = Caller::from_request(&request)?.clone();
+        let request = request.into_inner();
+        let expires_at = date_time(request.expires_at, "expires_at")?;
+        let (entity, minted) = self
+            .service
+            .create_api_key(&caller, &request.owner, &request.name, expires_at)
+            .await?;
+        if let Some(api_keys) = &self.api_keys {
+            api_keys.insert(&entity, &minted);
+        }
+
+        Ok(Response::new(CreateApiKeyResponse {
+            api_key: Some(entity.into()),
+            key: minted.key,
+        }))
+    }
+
+    /// Revoke an API key (admins only)
+    async fn revoke_api_key(
+        &self,
+        request: Request<RevokeApiKeyRequest>,
+    ) -> Result<Response<RevokeApiKeyResponse>, Status> {
+        log::info!("Revoke API key request from {:?}", request.remote_addr());
+
+        let caller = Caller::from_request(&request)?;
+        let entity = self
+            .service
+            .revoke_api_key(caller, &request.get_ref().key_id)
+            .await?;
+        if let Some(api_keys) = &self.api_keys {
+            api_keys.evict(entity.key_id);
+        }
+
+        Ok(Response::new(RevokeApiKeyResponse {
+            api_key: Some(entity.into()),
+        }))
+    }
+}
+
+#[tonic::async_trait]
+impl SharedStoryService for SharedStories {
+    /// Get a story and a page of its tasks through a share link
+    async fn get_shared_story(
+        &self,
+        request: Request<GetSharedStoryRequest>,
+    ) -> Result<Response<GetSharedStoryResponse>, Status> {
+        log::info!("Get shared story request from {:?}", request.remote_addr());
+
+        let request = request.get_ref();
+        let (story, page) = self
+            .service
+            .get_shared_story(&request.token, request.page_size, &request.page_token)
+            .await?;
+
+        let next_page_token = page.next_page_token();
+        let tasks = page.items.into_iter().map(|t| t.into()).collect();
+        let story = Story {
+            owner: String::new(),
+            ..story.into()
+        };
+
+        Ok(Response::new(GetSharedStoryResponse {
+            story: Some(story),
+            tasks,
+            next_page_token,
+        }))
+    }
 }
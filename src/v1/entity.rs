@@ -1,3 +1,7 @@
+use crate::v1::rank;
+use crate::v1::recurrence::Recurrence;
+
+use chrono::{DateTime, SecondsFormat, Utc};
 use std::fmt::{Display, Formatter, Result as FmtResult};
 use uuid::Uuid;
 
@@ -6,6 +10,26 @@ pub struct Story {
     pub story_id: Uuid,
     pub name: String,
     pub owner: String,
+    pub task_counts: TaskCounts,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Counts of live tasks, for progress reporting. Overdue tasks are open
+/// tasks whose due date has passed.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct TaskCounts {
+    pub total: i64,
+    pub completed: i64,
+    pub overdue: i64,
+}
+
+/// Totals across the live stories an owner has or that are shared with them.
+#[derive(Debug, Default, PartialEq)]
+pub struct OwnerSummary {
+    pub owner: String,
+    pub story_count: i64,
+    pub task_counts: TaskCounts,
 }
 
 #[derive(Debug, PartialEq)]
@@ -13,20 +37,299 @@ pub struct Task {
     pub task_id: Uuid,
     pub story_id: Uuid,
     pub name: String,
+    pub description: String,
     pub status: Status,
+    pub column: Option<String>,
+    pub priority: Priority,
+    pub rank: String,
+    pub label_ids: Vec<Uuid>,
+    pub subtask_total: i64,
+    pub subtask_done: i64,
+    pub due_at: Option<DateTime<Utc>>,
+    pub recurrence: Option<Recurrence>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub completed_at: Option<DateTime<Utc>>,
+}
+
+/// Longest story, task, label, checklist item, column or API key name, in
+/// characters.
+pub const MAX_NAME_LEN: usize = 100;
+
+/// Longest task description, in characters.
+pub const MAX_DESCRIPTION_LEN: usize = 10_000;
+
+/// Task field values supplied when creating or updating a task.
+#[derive(Debug, Default, PartialEq)]
+pub struct TaskFields {
+    pub name: String,
+    pub description: String,
+    pub priority: Option<Priority>,
+    pub due_at: Option<DateTime<Utc>>,
+    pub recurrence: Option<Recurrence>,
+}
+
+impl TaskFields {
+    /// Fields for a task with only a name.
+    pub fn named(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            ..Self::default()
+        }
+    }
+}
+
+/// A checklist item (subtask) under a task, in manual order.
+#[derive(Debug, PartialEq)]
+pub struct ChecklistItem {
+    pub item_id: Uuid,
+    pub task_id: Uuid,
+    pub name: String,
+    pub done: bool,
+    pub rank: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// When a reminder fires: at a fixed time, or a period before the task is due.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ReminderTrigger {
+    At(DateTime<Utc>),
+    BeforeDue(chrono::Duration),
+}
+
+/// A task reminder.
+#[derive(Debug, PartialEq)]
+pub struct Reminder {
+    pub reminder_id: Uuid,
+    pub task_id: Uuid,
+    pub trigger: ReminderTrigger,
+    /// When the reminder fires; `None` for an offset reminder on an undated task.
+    pub fire_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub sent_at: Option<DateTime<Utc>>,
+}
+
+/// An owner's label for tagging tasks across stories.
+#[derive(Debug, PartialEq)]
+pub struct Label {
+    pub label_id: Uuid,
+    pub owner: String,
+    pub name: String,
+    pub color: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A caller's access to a story, from least to most. Members are viewers,
+/// editors or admins; the owner may do everything.
+///
+/// - Viewers read the story, its board and its tasks with their checklists,
+///   dependencies and reminders.
+/// - Editors also create, change, move and delete tasks and everything under them.
+/// - Admins also change the story itself and share it.
+/// - The owner also deletes and restores the story.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Role {
+    Viewer,
+    Editor,
+    Admin,
+    Owner,
+}
+
+impl Display for Role {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match &self {
+            Self::Viewer => f.write_str("viewer"),
+            Self::Editor => f.write_str("editor"),
+            Self::Admin => f.write_str("admin"),
+            Self::Owner => f.write_str("owner"),
+        }
+    }
+}
+
+impl TryFrom<String> for Role {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        let value = value.trim().to_lowercase();
+        match value.as_str() {
+            "viewer" => Ok(Self::Viewer),
+            "editor" => Ok(Self::Editor),
+            "admin" => Ok(Self::Admin),
+            "owner" => Ok(Self::Owner),
+            _ => Err(format!("invalid role string: {}", value)),
+        }
+    }
+}
+
+/// Someone a story is shared with, and their role in it.
+#[derive(Debug, PartialEq)]
+pub struct StoryMember {
+    pub story_id: Uuid,
+    pub member: String,
+    pub role: Role,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// A caller's role in a story, and the story owner to act as.
+#[derive(Debug, PartialEq)]
+pub struct Access {
+    pub owner: String,
+    pub role: Role,
+}
+
+/// Something whose access follows the story it belongs to.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum StoryScope {
+    Story(Uuid),
+    Task(Uuid),
+    ChecklistItem(Uuid),
+    Reminder(Uuid),
+    ShareLink(Uuid),
+}
+
+impl StoryScope {
+    /// What kind of thing the scope names, for messages.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Story(_) => "story",
+            Self::Task(_) => "task",
+            Self::ChecklistItem(_) => "checklist item",
+            Self::Reminder(_) => "reminder",
+            Self::ShareLink(_) => "share link",
+        }
+    }
+
+    pub fn id(&self) -> Uuid {
+        match self {
+            Self::Story(id)
+            | Self::Task(id)
+            | Self::ChecklistItem(id)
+            | Self::Reminder(id)
+            | Self::ShareLink(id) => *id,
+        }
+    }
+}
+
+/// A link that shows a story and its tasks, read-only, to anyone holding its
+/// token. The token itself is only returned when minted.
+#[derive(Debug, PartialEq)]
+pub struct ShareLink {
+    pub link_id: Uuid,
+    pub story_id: Uuid,
+    pub created_by: String,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub revoked_at: Option<DateTime<Utc>>,
+    pub access_count: i64,
+    pub last_accessed_at: Option<DateTime<Utc>>,
 }
 
+/// A key for calling the API as its owner, without the key itself, which is
+/// only returned when minted.
 #[derive(Debug, PartialEq)]
+pub struct ApiKey {
+    pub key_id: Uuid,
+    pub owner: String,
+    pub name: String,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub revoked_at: Option<DateTime<Utc>>,
+    pub last_used_at: Option<DateTime<Utc>>,
+}
+
+/// Restricts task queries to tasks carrying any (or all) of a set of labels.
+/// An empty filter matches every task.
+#[derive(Debug, Default, PartialEq)]
+pub struct LabelFilter {
+    pub label_ids: Vec<Uuid>,
+    pub match_all: bool,
+}
+
+/// A soft-deleted item and when it was deleted.
+#[derive(Debug, PartialEq)]
+pub struct Trashed<T> {
+    pub item: T,
+    pub deleted_at: DateTime<Utc>,
+}
+
+/// An owner's deleted stories, and the tasks deleted on their own from live stories.
+#[derive(Debug, Default, PartialEq)]
+pub struct Trash {
+    pub stories: Vec<Trashed<Story>>,
+    pub tasks: Vec<Trashed<Task>>,
+}
+
+/// The live tasks a task is blocked by, and the live tasks it blocks.
+#[derive(Debug, Default, PartialEq)]
+pub struct TaskDependencies {
+    pub blocked_by: Vec<Task>,
+    pub blocking: Vec<Task>,
+}
+
+/// Story changes; `None` leaves a field unchanged.
+#[derive(Debug, Default, PartialEq)]
+pub struct StoryUpdate {
+    pub name: Option<String>,
+}
+
+/// Task changes; `None` leaves a field unchanged.
+#[derive(Debug, Default, PartialEq)]
+pub struct TaskUpdate {
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub priority: Option<Priority>,
+    pub due_at: Option<Option<DateTime<Utc>>>,
+    pub recurrence: Option<Option<Recurrence>>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum Status {
     Incomplete,
+    InProgress,
+    Blocked,
     Complete,
+    Cancelled,
+}
+
+impl Status {
+    /// All statuses, in default board order.
+    pub const ALL: [Status; 5] = [
+        Status::Incomplete,
+        Status::InProgress,
+        Status::Blocked,
+        Status::Complete,
+        Status::Cancelled,
+    ];
+
+    /// Whether a task in this status still counts as open work.
+    pub fn is_open(&self) -> bool {
+        !matches!(self, Status::Complete | Status::Cancelled)
+    }
+
+    /// Whether a task in this status may move to the next status.
+    pub fn can_transition_to(&self, next: Status) -> bool {
+        use Status::*;
+        match (self, next) {
+            (current, next) if *current == next => true,
+            (Incomplete | InProgress, _) => true,
+            (Blocked, Incomplete | InProgress | Cancelled) => true,
+            (Complete, Incomplete | InProgress) => true,
+            (Cancelled, Incomplete) => true,
+            _ => false,
+        }
+    }
 }
 
 impl Display for Status {
     fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
         match &self {
             Self::Incomplete => f.write_str("incomplete"),
+            Self::InProgress => f.write_str("in_progress"),
+            Self::Blocked => f.write_str("blocked"),
             Self::Complete => f.write_str("complete"),
+            Self::Cancelled => f.write_str("cancelled"),
         }
     }
 }
@@ -36,16 +339,293 @@ impl TryFrom<String> for Status {
 
     fn try_from(value: String) -> Result<Self, Self::Error> {
         let value = value.trim().to_lowercase();
-        if value == "incomplete" {
-            Ok(Self::Incomplete)
-        } else if value == "complete" {
-            Ok(Self::Complete)
-        } else {
-            Err(format!("invalid status string: {}", value))
+        match value.as_str() {
+            "incomplete" => Ok(Self::Incomplete),
+            "in_progress" => Ok(Self::InProgress),
+            "blocked" => Ok(Self::Blocked),
+            "complete" => Ok(Self::Complete),
+            "cancelled" => Ok(Self::Cancelled),
+            _ => Err(format!("invalid status string: {}", value)),
         }
     }
 }
 
+/// Task triage priority, from P0 (most urgent) to P3.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Priority {
+    P0,
+    P1,
+    #[default]
+    P2,
+    P3,
+}
+
+impl From<Priority> for i16 {
+    fn from(priority: Priority) -> Self {
+        priority as i16
+    }
+}
+
+impl TryFrom<i16> for Priority {
+    type Error = String;
+
+    fn try_from(value: i16) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(Self::P0),
+            1 => Ok(Self::P1),
+            2 => Ok(Self::P2),
+            3 => Ok(Self::P3),
+            _ => Err(format!("invalid priority: {}", value)),
+        }
+    }
+}
+
+/// Orderings for listing the tasks of a story. Every ordering breaks ties by
+/// creation time and then id.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TaskSort {
+    Created,
+    Priority,
+    Due,
+    #[default]
+    Rank,
+}
+
+/// Sort key for tasks without a due date, which sort after every dated task.
+pub const NO_DUE_DATE_KEY: &str = "infinity";
+
+impl TaskSort {
+    /// The leading keyset value for a task under this ordering, if the
+    /// ordering has one beyond creation time.
+    pub fn key(&self, task: &Task) -> Option<String> {
+        match self {
+            Self::Created => None,
+            Self::Priority => Some(i16::from(task.priority).to_string()),
+            Self::Due => Some(
+                task.due_at
+                    .map(|d| d.to_rfc3339_opts(SecondsFormat::Micros, true))
+                    .unwrap_or_else(|| NO_DUE_DATE_KEY.to_string()),
+            ),
+            Self::Rank => Some(task.rank.clone()),
+        }
+    }
+
+    /// Whether a page token key is well-formed for this ordering.
+    pub fn is_valid_key(&self, key: Option<&str>) -> bool {
+        match (self, key) {
+            (Self::Created, None) => true,
+            (Self::Priority, Some(key)) => key.parse::<i16>().is_ok(),
+            (Self::Due, Some(key)) => {
+                key == NO_DUE_DATE_KEY || DateTime::parse_from_rfc3339(key).is_ok()
+            }
+            (Self::Rank, Some(key)) => rank::is_valid(key),
+            _ => false,
+        }
+    }
+}
+
+/// A named board column that maps onto a task status.
+#[derive(Clone, Debug, PartialEq)]
+pub struct WorkflowColumn {
+    pub name: String,
+    pub status: Status,
+}
+
+/// The ordered board columns for a story.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Workflow {
+    pub columns: Vec<WorkflowColumn>,
+    pub custom: bool,
+}
+
+/// A board column along with the tasks it holds.
+#[derive(Debug, PartialEq)]
+pub struct BoardColumn {
+    pub column: WorkflowColumn,
+    pub tasks: Vec<Task>,
+}
+
+/// Where a task should move: the first column for a status, or a named column.
+#[derive(Debug, PartialEq)]
+pub enum StatusTarget {
+    Status(Status),
+    Column(String),
+}
+
+/// Where a task should move in the manual order, relative to a sibling task.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Placement {
+    Before(Uuid),
+    After(Uuid),
+}
+
+impl Placement {
+    /// The sibling task the placement is relative to.
+    pub fn sibling(&self) -> Uuid {
+        match self {
+            Self::Before(id) | Self::After(id) => *id,
+        }
+    }
+}
+
+/// A cap on the tasks a story may hold: open tasks when `column` is `None`,
+/// otherwise the tasks in a named workflow column.
+#[derive(Clone, Debug, PartialEq)]
+pub struct WipLimit {
+    pub column: Option<String>,
+    pub max_tasks: i64,
+}
+
+impl WipLimit {
+    /// Describe what the limit applies to.
+    pub fn scope(&self) -> String {
+        match &self.column {
+            None => "open tasks".to_string(),
+            Some(name) => format!("column {}", name),
+        }
+    }
+}
+
+/// A move that would break a WIP limit.
+#[derive(Debug, PartialEq)]
+pub struct WipViolation {
+    pub scope: String,
+    pub limit: i64,
+    pub count: i64,
+}
+
+impl Display for StatusTarget {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match &self {
+            Self::Status(status) => status.fmt(f),
+            Self::Column(name) => f.write_str(name),
+        }
+    }
+}
+
+impl Default for Workflow {
+    /// The workflow used by stories without custom columns: one column per status.
+    fn default() -> Self {
+        let columns = Status::ALL
+            .into_iter()
+            .map(|status| WorkflowColumn {
+                name: status.to_string(),
+                status,
+            })
+            .collect();
+        Self {
+            columns,
+            custom: false,
+        }
+    }
+}
+
+impl Workflow {
+    /// Create a custom workflow, ensuring tasks can always be opened and completed.
+    pub fn custom(columns: Vec<WorkflowColumn>) -> Result<Self, String> {
+        for (i, column) in columns.iter().enumerate() {
+            if columns[..i].iter().any(|c| c.name == column.name) {
+                return Err(format!("duplicate workflow column: {}", column.name));
+            }
+        }
+        for status in [Status::Incomplete, Status::Complete] {
+            if !columns.iter().any(|c| c.status == status) {
+                return Err(format!("workflow requires a {} column", status));
+            }
+        }
+        Ok(Self {
+            columns,
+            custom: true,
+        })
+    }
+
+    /// Find the column a move targets.
+    pub fn resolve(&self, target: &StatusTarget) -> Option<&WorkflowColumn> {
+        match target {
+            StatusTarget::Status(status) => self.columns.iter().find(|c| c.status == *status),
+            StatusTarget::Column(name) => self.columns.iter().find(|c| c.name == *name),
+        }
+    }
+
+    /// Whether a task in the current status may move into a column. Custom
+    /// workflows allow any move between their columns.
+    pub fn allows(&self, current: Status, column: &WorkflowColumn) -> bool {
+        self.custom || current.can_transition_to(column.status)
+    }
+
+    /// The index of the column a task sits in. Tasks without a (known) column
+    /// land in the first column for their status, or the first column.
+    pub fn column_index(&self, status: Status, column: Option<&str>) -> usize {
+        let named = column.and_then(|name| self.columns.iter().position(|c| c.name == name));
+        named
+            .or_else(|| self.columns.iter().position(|c| c.status == status))
+            .unwrap_or(0)
+    }
+
+    /// Check that a task entering a column keeps the story within its WIP limits.
+    /// `others` holds the (status, column) of every other task in the story, and
+    /// `from` where the moving task sits now (`None` for new tasks). Limits only
+    /// apply to moves that add a task to their scope.
+    pub fn check_wip_limits(
+        &self,
+        limits: &[WipLimit],
+        others: &[(Status, Option<String>)],
+        from: Option<(Status, Option<&str>)>,
+        to: &WorkflowColumn,
+    ) -> Result<(), WipViolation> {
+        let to_index = self.column_index(to.status, Some(&to.name));
+        for limit in limits {
+            let count = match &limit.column {
+                None => {
+                    if !to.status.is_open() || from.is_some_and(|(status, _)| status.is_open()) {
+                        continue;
+                    }
+                    others.iter().filter(|(status, _)| status.is_open()).count()
+                }
+                Some(name) => {
+                    let from_index = from.map(|(status, column)| self.column_index(status, column));
+                    if *name != self.columns[to_index].name || from_index == Some(to_index) {
+                        continue;
+                    }
+                    others
+                        .iter()
+                        .filter(|(status, column)| {
+                            self.column_index(*status, column.as_deref()) == to_index
+                        })
+                        .count()
+                }
+            };
+            let count = count as i64;
+            if count >= limit.max_tasks {
+                return Err(WipViolation {
+                    scope: limit.scope(),
+                    limit: limit.max_tasks,
+                    count,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Group tasks into board columns, keeping the task order within each column.
+    pub fn board(self, tasks: Vec<Task>) -> Vec<BoardColumn> {
+        let mut board: Vec<BoardColumn> = self
+            .columns
+            .iter()
+            .cloned()
+            .map(|column| BoardColumn {
+                column,
+                tasks: Vec::new(),
+            })
+            .collect();
+        for task in tasks {
+            let index = self.column_index(task.status, task.column.as_deref());
+            board[index].tasks.push(task);
+        }
+        board
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -66,5 +646,206 @@ mod tests {
     fn status_to_string() {
         assert_eq!(Status::Complete.to_string(), "complete");
         assert_eq!(Status::Incomplete.to_string(), "incomplete");
+        assert_eq!(Status::InProgress.to_string(), "in_progress");
+    }
+
+    #[test]
+    fn role_round_trip() {
+        use Role::*;
+        for role in [Viewer, Editor, Admin, Owner] {
+            assert_eq!(Role::try_from(role.to_string()).unwrap(), role);
+        }
+        assert!(Role::try_from("guest".to_string()).is_err());
+        assert!(Viewer < Editor && Editor < Admin && Admin < Owner);
+    }
+
+    #[test]
+    fn priority_round_trip() {
+        use Priority::*;
+        for priority in [P0, P1, P2, P3] {
+            assert_eq!(Priority::try_from(i16::from(priority)).unwrap(), priority);
+        }
+        assert!(Priority::try_from(4).is_err());
+        assert_eq!(Priority::default(), P2);
+    }
+
+    #[test]
+    fn task_sort_keys() {
+        let mut task = task(Status::Incomplete, None);
+        task.priority = Priority::P0;
+        assert_eq!(TaskSort::Created.key(&task), None);
+        assert_eq!(TaskSort::Priority.key(&task), Some("0".to_string()));
+        assert_eq!(TaskSort::Due.key(&task), Some("infinity".to_string()));
+        assert_eq!(TaskSort::Rank.key(&task), Some("i".to_string()));
+        for sort in [
+            TaskSort::Created,
+            TaskSort::Priority,
+            TaskSort::Due,
+            TaskSort::Rank,
+        ] {
+            assert!(sort.is_valid_key(sort.key(&task).as_deref()));
+        }
+        task.due_at = Some(Utc::now());
+        assert!(TaskSort::Due.is_valid_key(TaskSort::Due.key(&task).as_deref()));
+        assert!(!TaskSort::Created.is_valid_key(Some("1")));
+        assert!(!TaskSort::Priority.is_valid_key(None));
+        assert!(!TaskSort::Due.is_valid_key(Some("soon")));
+        assert!(!TaskSort::Rank.is_valid_key(Some("A0")));
+    }
+
+    #[test]
+    fn status_round_trip() {
+        use Status::*;
+        for status in [Incomplete, InProgress, Blocked, Complete, Cancelled] {
+            assert_eq!(Status::try_from(status.to_string()).unwrap(), status);
+        }
+    }
+
+    #[test]
+    fn status_transitions() {
+        use Status::*;
+        assert!(Incomplete.can_transition_to(Complete));
+        assert!(Complete.can_transition_to(Incomplete));
+        assert!(Complete.can_transition_to(Complete));
+        assert!(Cancelled.can_transition_to(Incomplete));
+        assert!(!Blocked.can_transition_to(Complete));
+        assert!(!Cancelled.can_transition_to(Complete));
+        assert!(!Complete.can_transition_to(Cancelled));
+    }
+
+    fn column(name: &str, status: Status) -> WorkflowColumn {
+        WorkflowColumn {
+            name: name.to_string(),
+            status,
+        }
+    }
+
+    fn task(status: Status, column: Option<&str>) -> Task {
+        Task {
+            task_id: Uuid::new_v4(),
+            story_id: Uuid::nil(),
+            name: "task".to_string(),
+            description: String::new(),
+            status,
+            column: column.map(String::from),
+            priority: Priority::default(),
+            rank: "i".to_string(),
+            label_ids: Vec::new(),
+            subtask_total: 0,
+            subtask_done: 0,
+            due_at: None,
+            recurrence: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            completed_at: None,
+        }
+    }
+
+    fn kanban() -> Workflow {
+        Workflow::custom(vec![
+            column("backlog", Status::Incomplete),
+            column("dev", Status::InProgress),
+            column("review", Status::InProgress),
+            column("done", Status::Complete),
+        ])
+        .unwrap()
+    }
+
+    #[test]
+    fn workflow_custom_validation() {
+        let err = Workflow::custom(vec![column("todo", Status::Incomplete)]).unwrap_err();
+        assert_eq!(err, "workflow requires a complete column");
+
+        let err = Workflow::custom(vec![
+            column("todo", Status::Incomplete),
+            column("todo", Status::Complete),
+        ])
+        .unwrap_err();
+        assert_eq!(err, "duplicate workflow column: todo");
+    }
+
+    #[test]
+    fn workflow_resolve() {
+        let workflow = kanban();
+        let target = StatusTarget::Status(Status::InProgress);
+        assert_eq!(workflow.resolve(&target).unwrap().name, "dev");
+        let target = StatusTarget::Column("review".to_string());
+        assert_eq!(workflow.resolve(&target).unwrap().name, "review");
+        assert!(workflow
+            .resolve(&StatusTarget::Status(Status::Blocked))
+            .is_none());
+
+        let workflow = Workflow::default();
+        let target = StatusTarget::Status(Status::Blocked);
+        assert_eq!(workflow.resolve(&target).unwrap().name, "blocked");
+    }
+
+    #[test]
+    fn workflow_allows() {
+        let done = column("done", Status::Complete);
+        assert!(kanban().allows(Status::Blocked, &done));
+        assert!(!Workflow::default().allows(Status::Blocked, &done));
+    }
+
+    #[test]
+    fn workflow_board() {
+        let tasks = vec![
+            task(Status::Incomplete, None),
+            task(Status::InProgress, Some("review")),
+            task(Status::InProgress, Some("gone")),
+            task(Status::Blocked, None),
+        ];
+        let board = kanban().board(tasks);
+        let counts: Vec<_> = board.iter().map(|c| c.tasks.len()).collect();
+        assert_eq!(counts, vec![2, 1, 1, 0]);
+    }
+
+    #[test]
+    fn wip_limits() {
+        let workflow = kanban();
+        let dev = column("dev", Status::InProgress);
+        let done = column("done", Status::Complete);
+        let limits = vec![
+            WipLimit {
+                column: None,
+                max_tasks: 3,
+            },
+            WipLimit {
+                column: Some("dev".to_string()),
+                max_tasks: 1,
+            },
+        ];
+        let others = vec![
+            (Status::Incomplete, None),
+            (Status::InProgress, Some("dev".to_string())),
+            (Status::Complete, None),
+        ];
+
+        // A new task fits under the open cap but not into the full dev column
+        let backlog = column("backlog", Status::Incomplete);
+        assert!(workflow
+            .check_wip_limits(&limits, &others, None, &backlog)
+            .is_ok());
+        let err = workflow
+            .check_wip_limits(&limits, &others, None, &dev)
+            .unwrap_err();
+        assert_eq!(err.scope, "column dev");
+        assert_eq!((err.limit, err.count), (1, 1));
+
+        // Reopening a task counts against the open cap
+        let others = vec![(Status::Incomplete, None); 3];
+        let err = workflow
+            .check_wip_limits(&limits, &others, Some((Status::Complete, None)), &backlog)
+            .unwrap_err();
+        assert_eq!(err.scope, "open tasks");
+        assert_eq!((err.limit, err.count), (3, 3));
+
+        // Moves between open columns and into closed columns are not capped
+        assert!(workflow
+            .check_wip_limits(&limits, &others, Some((Status::Incomplete, None)), &backlog)
+            .is_ok());
+        assert!(workflow
+            .check_wip_limits(&limits, &others, None, &done)
+            .is_ok());
     }
 }
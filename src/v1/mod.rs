@@ -3,5 +3,9 @@ pub mod proto {
 }
 pub mod api;
 pub mod entity;
+pub mod page;
+pub mod rank;
+pub mod recurrence;
 pub mod repo;
 pub mod service;
+pub mod share;
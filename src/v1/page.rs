@@ -0,0 +1,165 @@
+use crate::{Error, Result};
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use chrono::{DateTime, TimeZone, Utc};
+use uuid::Uuid;
+
+/// Page size used when a request does not specify one.
+pub const DEFAULT_PAGE_SIZE: i64 = 50;
+
+/// Largest page size a request may ask for.
+pub const MAX_PAGE_SIZE: i64 = 100;
+
+/// Keyset position of the last row on a page, with an optional leading sort
+/// key for orderings other than creation time.
+#[derive(Debug, PartialEq)]
+pub struct Cursor {
+    pub key: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub id: Uuid,
+}
+
+/// A page of results with an optional cursor for the next page.
+#[derive(Debug, PartialEq)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub next_cursor: Option<Cursor>,
+}
+
+impl Cursor {
+    /// Create a cursor from a row's keyset values.
+    pub fn new(created_at: DateTime<Utc>, id: Uuid) -> Self {
+        Self {
+            key: None,
+            created_at,
+            id,
+        }
+    }
+
+    /// Set the leading sort key of the cursor.
+    pub fn with_key(self, key: Option<String>) -> Self {
+        Self { key, ..self }
+    }
+
+    /// Encode the cursor as an opaque page token.
+    pub fn encode(&self) -> String {
+        let mut raw = format!("{}:{}", self.created_at.timestamp_micros(), self.id);
+        if let Some(key) = &self.key {
+            raw = format!("{}:{}", raw, key);
+        }
+        URL_SAFE_NO_PAD.encode(raw)
+    }
+
+    /// Decode an opaque page token. An empty token means the first page.
+    pub fn decode(token: &str) -> Result<Option<Self>> {
+        let token = token.trim();
+        if token.is_empty() {
+            return Ok(None);
+        }
+
+        let invalid = || Error::InvalidArgument {
+            message: format!("invalid page token: {}", token),
+        };
+
+        let bytes = URL_SAFE_NO_PAD.decode(token).map_err(|_| invalid())?;
+        let raw = String::from_utf8(bytes).map_err(|_| invalid())?;
+        let mut parts = raw.splitn(3, ':');
+        let micros = parts.next().ok_or_else(invalid)?;
+        let id = parts.next().ok_or_else(invalid)?;
+        let key = parts.next().map(String::from);
+
+        let micros = micros.parse().map_err(|_| invalid())?;
+        let created_at = Utc.timestamp_micros(micros).single().ok_or_else(invalid)?;
+        let id = Uuid::parse_str(id).map_err(|_| invalid())?;
+
+        Ok(Some(Self::new(created_at, id).with_key(key)))
+    }
+}
+
+impl<T> Page<T> {
+    /// Resolve a requested page size, using the default for zero and capping at the max.
+    pub fn size(requested: u32) -> i64 {
+        match requested as i64 {
+            0 => DEFAULT_PAGE_SIZE,
+            n => n.min(MAX_PAGE_SIZE),
+        }
+    }
+
+    /// Build a page from rows fetched with `limit + 1`, where the extra row
+    /// signals that another page exists.
+    pub fn from_rows(mut rows: Vec<T>, limit: i64, key: impl Fn(&T) -> Cursor) -> Self {
+        let mut next_cursor = None;
+        if rows.len() as i64 > limit {
+            rows.truncate(limit as usize);
+            next_cursor = rows.last().map(key);
+        }
+        Self {
+            items: rows,
+            next_cursor,
+        }
+    }
+
+    /// The opaque token for the next page, or an empty string on the last page.
+    pub fn next_page_token(&self) -> String {
+        self.next_cursor
+            .as_ref()
+            .map(Cursor::encode)
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cursor_round_trip() {
+        let created_at = Utc.timestamp_micros(1703361425123456).unwrap();
+        let cursor = Cursor::new(created_at, Uuid::new_v4());
+        let token = cursor.encode();
+        assert_eq!(Cursor::decode(&token).unwrap(), Some(cursor));
+    }
+
+    #[test]
+    fn cursor_round_trip_with_key() {
+        let created_at = Utc.timestamp_micros(1703361425123456).unwrap();
+        let cursor = Cursor::new(created_at, Uuid::new_v4()).with_key(Some("0000001i".into()));
+        let token = cursor.encode();
+        assert_eq!(Cursor::decode(&token).unwrap(), Some(cursor));
+    }
+
+    #[test]
+    fn cursor_decode_empty() {
+        assert_eq!(Cursor::decode("  ").unwrap(), None);
+    }
+
+    #[test]
+    fn cursor_decode_invalid() {
+        let error = Cursor::decode("not-a-token").unwrap_err();
+        assert!(error
+            .to_string()
+            .starts_with("invalid argument: invalid page token"));
+    }
+
+    #[test]
+    fn page_size_bounds() {
+        assert_eq!(Page::<()>::size(0), DEFAULT_PAGE_SIZE);
+        assert_eq!(Page::<()>::size(10), 10);
+        assert_eq!(Page::<()>::size(1000), MAX_PAGE_SIZE);
+    }
+
+    #[test]
+    fn page_from_rows() {
+        let now = Utc::now();
+        let key = |_: &u8| Cursor::new(now, Uuid::nil());
+
+        let page = Page::from_rows(vec![1, 2, 3], 2, key);
+        assert_eq!(page.items, vec![1, 2]);
+        assert!(page.next_cursor.is_some());
+
+        let page = Page::from_rows(vec![1, 2], 2, key);
+        assert_eq!(page.items, vec![1, 2]);
+        assert_eq!(page.next_page_token(), "");
+    }
+}
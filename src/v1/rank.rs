@@ -0,0 +1,120 @@
+//! Lexicographic ranks for manual task ordering.
+//!
+//! A rank is a base-36 fraction written with the digits `0-9a-z`, so ranks
+//! compare correctly as plain strings (under the "C" collation). A rank never
+//! ends in `0`, which guarantees there is always room for another rank between
+//! any two neighbours without renumbering.
+
+const DIGITS: &[u8] = b"0123456789abcdefghijklmnopqrstuvwxyz";
+const BASE: usize = DIGITS.len();
+
+/// Whether a string is a well-formed rank.
+pub fn is_valid(rank: &str) -> bool {
+    !rank.is_empty() && !rank.ends_with('0') && rank.bytes().all(|b| digit(b).is_some())
+}
+
+/// A rank strictly between two neighbours. `None` stands for the start or the
+/// end of the list; both bounds must be valid ranks with `before < after`.
+pub fn between(before: Option<&str>, after: Option<&str>) -> String {
+    let before = before.unwrap_or("").as_bytes();
+    let after = after.map(str::as_bytes);
+    let rank = midpoint(before, after);
+    String::from_utf8(rank).expect("rank digits are ascii")
+}
+
+fn digit(b: u8) -> Option<usize> {
+    DIGITS.iter().position(|d| *d == b)
+}
+
+fn midpoint(a: &[u8], b: Option<&[u8]>) -> Vec<u8> {
+    let at = |i: usize| a.get(i).copied().unwrap_or(b'0');
+
+    if let Some(b) = b {
+        // Keep the shared prefix, treating a missing digit in `a` as zero.
+        let n = b
+            .iter()
+            .enumerate()
+            .take_while(|(i, d)| at(*i) == **d)
+            .count();
+        if n > 0 {
+            let mut rank = b[..n].to_vec();
+            rank.extend(midpoint(a.get(n..).unwrap_or(&[]), Some(&b[n..])));
+            return rank;
+        }
+    }
+
+    let da = digit(at(0)).unwrap_or(0);
+    let db = b
+        .and_then(|b| b.first())
+        .and_then(|d| digit(*d))
+        .unwrap_or(BASE);
+    if db - da > 1 {
+        return vec![DIGITS[(da + db) / 2]];
+    }
+
+    // Adjacent leading digits: a longer `b` can be cut short, otherwise
+    // descend into the digits after `a`'s leading digit.
+    match b {
+        Some(b) if b.len() > 1 => vec![b[0]],
+        _ => {
+            let mut rank = vec![DIGITS[da]];
+            rank.extend(midpoint(a.get(1..).unwrap_or(&[]), None));
+            rank
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rank_between_ends() {
+        assert_eq!(between(None, None), "i");
+        assert!(between(Some("i"), None).as_str() > "i");
+        assert!(between(None, Some("i")).as_str() < "i");
+    }
+
+    #[test]
+    fn rank_between_neighbours() {
+        for (a, b) in [
+            ("a", "b"),
+            ("a", "a1"),
+            ("az", "b"),
+            ("00001i", "00002i"),
+            ("1", "11"),
+        ] {
+            let rank = between(Some(a), Some(b));
+            assert!(
+                a < rank.as_str() && rank.as_str() < b,
+                "{} < {} < {}",
+                a,
+                rank,
+                b
+            );
+            assert!(is_valid(&rank));
+        }
+    }
+
+    #[test]
+    fn rank_repeated_inserts() {
+        // Keep inserting at the front, the back and just after the first rank.
+        let mut ranks = vec![between(None, None)];
+        for _ in 0..200 {
+            ranks.insert(0, between(None, Some(&ranks[0])));
+            ranks.push(between(ranks.last().map(String::as_str), None));
+            let rank = between(Some(&ranks[0]), Some(&ranks[1]));
+            ranks.insert(1, rank);
+        }
+        assert!(ranks.windows(2).all(|w| w[0] < w[1]));
+        assert!(ranks.iter().all(|r| is_valid(r)));
+    }
+
+    #[test]
+    fn rank_validity() {
+        assert!(is_valid("00000001i"));
+        assert!(!is_valid(""));
+        assert!(!is_valid("a0"));
+        assert!(!is_valid("A"));
+    }
+}
@@ -0,0 +1,399 @@
+//! Recurrence rules for repeating tasks.
+//!
+//! A small subset of the iCalendar RRULE syntax is supported:
+//!
+//! - `FREQ=DAILY;INTERVAL=n`
+//! - `FREQ=WEEKLY;INTERVAL=n;BYDAY=MO,WE,FR`
+//! - `FREQ=MONTHLY;INTERVAL=n;BYMONTHDAY=d`
+//!
+//! `INTERVAL` defaults to 1. Without `BYDAY` a weekly rule repeats on the same
+//! weekday, and without `BYMONTHDAY` a monthly rule repeats on the same day of
+//! the month as the first due date, which later occurrences store as their
+//! `BYMONTHDAY`. Days past the end of a short month fall on its last day.
+
+use chrono::{DateTime, Datelike, Duration, NaiveDate, TimeZone, Utc, Weekday};
+use std::fmt;
+use std::str::FromStr;
+
+const MAX_INTERVAL: u32 = 999;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+}
+
+/// A parsed recurrence rule.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Recurrence {
+    pub frequency: Frequency,
+    pub interval: u32,
+    /// Weekdays for weekly rules, in Monday-first order.
+    pub by_day: Vec<Weekday>,
+    /// Day of the month for monthly rules.
+    pub by_month_day: Option<u32>,
+}
+
+impl Recurrence {
+    /// The first occurrence strictly after `from`, keeping its time of day, or
+    /// `None` past the end of the representable date range.
+    pub fn next(&self, from: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let interval = i64::from(self.interval);
+        match self.frequency {
+            Frequency::Daily => from.checked_add_signed(Duration::days(interval)),
+            Frequency::Weekly if self.by_day.is_empty() => {
+                from.checked_add_signed(Duration::weeks(interval))
+            }
+            Frequency::Weekly => {
+                let today = from.weekday().num_days_from_monday();
+                match self
+                    .by_day
+                    .iter()
+                    .map(Weekday::num_days_from_monday)
+                    .find(|day| *day > today)
+                {
+                    Some(day) => from.checked_add_signed(Duration::days(i64::from(day - today))),
+                    None => {
+                        let monday = from - Duration::days(i64::from(today));
+                        let first = self.by_day[0].num_days_from_monday();
+                        monday.checked_add_signed(
+                            Duration::weeks(interval) + Duration::days(i64::from(first)),
+                        )
+                    }
+                }
+            }
+            Frequency::Monthly => {
+                let day = self.by_month_day.unwrap_or(from.day());
+                let this_month = month_day(from.year(), from.month(), day);
+                let date = match this_month {
+                    Some(date) if self.by_month_day.is_some() && date > from.date_naive() => date,
+                    _ => {
+                        let months = from.year() * 12 + from.month0() as i32 + self.interval as i32;
+                        month_day(months / 12, months as u32 % 12 + 1, day)?
+                    }
+                };
+                Some(Utc.from_utc_datetime(&date.and_time(from.time())))
+            }
+        }
+    }
+
+    /// The rule with a monthly rule's day pinned to the day of `from`, so an
+    /// occurrence clamped to the end of a short month does not move every
+    /// later occurrence to that day too.
+    pub fn anchored(&self, from: DateTime<Utc>) -> Self {
+        let mut rule = self.clone();
+        if rule.frequency == Frequency::Monthly && rule.by_month_day.is_none() {
+            rule.by_month_day = Some(from.day());
+        }
+        rule
+    }
+
+    /// The due date for the occurrence after one due at `due_at` (or, for
+    /// undated tasks, completed at `now`). Occurrences that are already in the
+    /// past are skipped. `None` when the next occurrence is out of range.
+    pub fn next_due(
+        &self,
+        due_at: Option<DateTime<Utc>>,
+        now: DateTime<Utc>,
+    ) -> Option<DateTime<Utc>> {
+        let from = due_at.unwrap_or(now);
+        let rule = self.anchored(from);
+        let mut next = rule.next(from)?;
+        while next <= now {
+            next = rule.next(next)?;
+        }
+        Some(next)
+    }
+}
+
+/// A day in a month, clamped to the month's last day.
+fn month_day(year: i32, month: u32, day: u32) -> Option<NaiveDate> {
+    (1..=day)
+        .rev()
+        .find_map(|day| NaiveDate::from_ymd_opt(year, month, day))
+}
+
+fn weekday(value: &str) -> Result<Weekday, String> {
+    match value {
+        "MO" => Ok(Weekday::Mon),
+        "TU" => Ok(Weekday::Tue),
+        "WE" => Ok(Weekday::Wed),
+        "TH" => Ok(Weekday::Thu),
+        "FR" => Ok(Weekday::Fri),
+        "SA" => Ok(Weekday::Sat),
+        "SU" => Ok(Weekday::Sun),
+        _ => Err(format!("invalid BYDAY value: {}", value)),
+    }
+}
+
+fn weekday_code(day: Weekday) -> &'static str {
+    match day {
+        Weekday::Mon => "MO",
+        Weekday::Tue => "TU",
+        Weekday::Wed => "WE",
+        Weekday::Thu => "TH",
+        Weekday::Fri => "FR",
+        Weekday::Sat => "SA",
+        Weekday::Sun => "SU",
+    }
+}
+
+impl FromStr for Recurrence {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let rule = value.trim().to_ascii_uppercase();
+        let rule = rule.strip_prefix("RRULE:").unwrap_or(&rule);
+
+        let mut frequency = None;
+        let mut interval = None;
+        let mut by_day = None;
+        let mut by_month_day = None;
+        for part in rule.split(';').filter(|p| !p.is_empty()) {
+            let (key, value) = part
+                .split_once('=')
+                .ok_or_else(|| format!("invalid recurrence rule part: {}", part))?;
+            let duplicate = match key {
+                "FREQ" => frequency
+                    .replace(match value {
+                        "DAILY" => Frequency::Daily,
+                        "WEEKLY" => Frequency::Weekly,
+                        "MONTHLY" => Frequency::Monthly,
+                        _ => return Err(format!("unsupported FREQ: {}", value)),
+                    })
+                    .is_some(),
+                "INTERVAL" => interval
+                    .replace(
+                        value
+                            .parse::<u32>()
+                            .ok()
+                            .filter(|n| (1..=MAX_INTERVAL).contains(n))
+                            .ok_or_else(|| format!("invalid INTERVAL: {}", value))?,
+                    )
+                    .is_some(),
+                "BYDAY" => {
+                    let mut days = value
+                        .split(',')
+                        .map(weekday)
+                        .collect::<Result<Vec<_>, _>>()?;
+                    days.sort_by_key(Weekday::num_days_from_monday);
+                    days.dedup();
+                    by_day.replace(days).is_some()
+                }
+                "BYMONTHDAY" => by_month_day
+                    .replace(
+                        value
+                            .parse::<u32>()
+                            .ok()
+                            .filter(|d| (1..=31).contains(d))
+                            .ok_or_else(|| format!("invalid BYMONTHDAY: {}", value))?,
+                    )
+                    .is_some(),
+                _ => return Err(format!("unsupported recurrence rule part: {}", key)),
+            };
+            if duplicate {
+                return Err(format!("duplicate recurrence rule part: {}", key));
+            }
+        }
+
+        let frequency = frequency.ok_or_else(|| "FREQ is required".to_string())?;
+        if by_day.is_some() && frequency != Frequency::Weekly {
+            return Err("BYDAY is only supported for weekly rules".to_string());
+        }
+        if by_month_day.is_some() && frequency != Frequency::Monthly {
+            return Err("BYMONTHDAY is only supported for monthly rules".to_string());
+        }
+
+        Ok(Self {
+            frequency,
+            interval: interval.unwrap_or(1),
+            by_day: by_day.unwrap_or_default(),
+            by_month_day,
+        })
+    }
+}
+
+impl fmt::Display for Recurrence {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let frequency = match self.frequency {
+            Frequency::Daily => "DAILY",
+            Frequency::Weekly => "WEEKLY",
+            Frequency::Monthly => "MONTHLY",
+        };
+        write!(f, "FREQ={}", frequency)?;
+        if self.interval != 1 {
+            write!(f, ";INTERVAL={}", self.interval)?;
+        }
+        if !self.by_day.is_empty() {
+            let days: Vec<_> = self.by_day.iter().map(|d| weekday_code(*d)).collect();
+            write!(f, ";BYDAY={}", days.join(","))?;
+        }
+        if let Some(day) = self.by_month_day {
+            write!(f, ";BYMONTHDAY={}", day)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(date: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(date)
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    fn rule(value: &str) -> Recurrence {
+        value.parse().unwrap()
+    }
+
+    #[test]
+    fn recurrence_parse_and_display() {
+        assert_eq!(rule("FREQ=DAILY").to_string(), "FREQ=DAILY");
+        assert_eq!(
+            rule("rrule:freq=weekly;byday=fr,mo,fr;interval=2").to_string(),
+            "FREQ=WEEKLY;INTERVAL=2;BYDAY=MO,FR"
+        );
+        assert_eq!(
+            rule("FREQ=MONTHLY;BYMONTHDAY=31").to_string(),
+            "FREQ=MONTHLY;BYMONTHDAY=31"
+        );
+    }
+
+    #[test]
+    fn recurrence_parse_errors() {
+        for value in [
+            "",
+            "INTERVAL=2",
+            "FREQ=YEARLY",
+            "FREQ=DAILY;INTERVAL=0",
+            "FREQ=DAILY;FREQ=WEEKLY",
+            "FREQ=DAILY;BYDAY=MO",
+            "FREQ=WEEKLY;BYDAY=XX",
+            "FREQ=WEEKLY;BYMONTHDAY=1",
+            "FREQ=MONTHLY;BYMONTHDAY=32",
+            "FREQ=DAILY;COUNT=3",
+            "FREQ",
+        ] {
+            assert!(value.parse::<Recurrence>().is_err(), "{}", value);
+        }
+    }
+
+    #[test]
+    fn recurrence_next_daily_and_weekly() {
+        // 2026-10-14 is a Wednesday
+        let from = at("2026-10-14T09:30:00Z");
+        assert_eq!(
+            rule("FREQ=DAILY").next(from).unwrap(),
+            at("2026-10-15T09:30:00Z")
+        );
+        assert_eq!(
+            rule("FREQ=DAILY;INTERVAL=3").next(from).unwrap(),
+            at("2026-10-17T09:30:00Z")
+        );
+        assert_eq!(
+            rule("FREQ=WEEKLY").next(from).unwrap(),
+            at("2026-10-21T09:30:00Z")
+        );
+        let weekdays = rule("FREQ=WEEKLY;BYDAY=MO,WE,FR");
+        assert_eq!(weekdays.next(from).unwrap(), at("2026-10-16T09:30:00Z"));
+        assert_eq!(
+            weekdays.next(at("2026-10-16T09:30:00Z")).unwrap(),
+            at("2026-10-19T09:30:00Z")
+        );
+        let fortnightly = rule("FREQ=WEEKLY;INTERVAL=2;BYDAY=MO,TU");
+        assert_eq!(fortnightly.next(from).unwrap(), at("2026-10-26T09:30:00Z"));
+    }
+
+    #[test]
+    fn recurrence_next_monthly() {
+        let from = at("2026-01-31T08:00:00Z");
+        assert_eq!(
+            rule("FREQ=MONTHLY").next(from).unwrap(),
+            at("2026-02-28T08:00:00Z")
+        );
+        let end_of_month = rule("FREQ=MONTHLY;BYMONTHDAY=31");
+        assert_eq!(end_of_month.next(from).unwrap(), at("2026-02-28T08:00:00Z"));
+        assert_eq!(
+            end_of_month.next(at("2026-02-28T08:00:00Z")).unwrap(),
+            at("2026-03-31T08:00:00Z")
+        );
+        let quarterly = rule("FREQ=MONTHLY;INTERVAL=3;BYMONTHDAY=15");
+        assert_eq!(
+            quarterly.next(at("2026-11-20T08:00:00Z")).unwrap(),
+            at("2027-02-15T08:00:00Z")
+        );
+        assert_eq!(
+            quarterly.next(at("2026-11-02T08:00:00Z")).unwrap(),
+            at("2026-11-15T08:00:00Z")
+        );
+    }
+
+    #[test]
+    fn recurrence_next_monthly_keeps_anchor_day() {
+        let from = at("2026-01-31T08:00:00Z");
+        let monthly = rule("FREQ=MONTHLY").anchored(from);
+        assert_eq!(monthly.to_string(), "FREQ=MONTHLY;BYMONTHDAY=31");
+        let mut next = from;
+        let mut dates = Vec::new();
+        for _ in 0..4 {
+            next = monthly.next(next).unwrap();
+            dates.push(next);
+        }
+        assert_eq!(
+            dates,
+            vec![
+                at("2026-02-28T08:00:00Z"),
+                at("2026-03-31T08:00:00Z"),
+                at("2026-04-30T08:00:00Z"),
+                at("2026-05-31T08:00:00Z"),
+            ]
+        );
+
+        // Skipping past occurrences keeps the anchor day too
+        let now = at("2026-04-10T12:00:00Z");
+        assert_eq!(
+            rule("FREQ=MONTHLY").next_due(Some(from), now).unwrap(),
+            at("2026-04-30T08:00:00Z")
+        );
+
+        // Rules with a day, and other frequencies, are left as they are
+        let fifteenth = rule("FREQ=MONTHLY;BYMONTHDAY=15");
+        assert_eq!(fifteenth.anchored(from), fifteenth);
+        assert_eq!(rule("FREQ=DAILY").anchored(from), rule("FREQ=DAILY"));
+    }
+
+    #[test]
+    fn recurrence_next_due_skips_past_occurrences() {
+        let now = at("2026-10-17T12:00:00Z");
+        let daily = rule("FREQ=DAILY");
+        assert_eq!(
+            daily
+                .next_due(Some(at("2026-10-10T09:00:00Z")), now)
+                .unwrap(),
+            at("2026-10-18T09:00:00Z")
+        );
+        assert_eq!(
+            daily
+                .next_due(Some(at("2026-10-20T09:00:00Z")), now)
+                .unwrap(),
+            at("2026-10-21T09:00:00Z")
+        );
+        assert_eq!(
+            daily.next_due(None, now).unwrap(),
+            at("2026-10-18T12:00:00Z")
+        );
+    }
+
+    #[test]
+    fn recurrence_next_out_of_range() {
+        let end = DateTime::<Utc>::MAX_UTC;
+        for value in ["FREQ=DAILY", "FREQ=WEEKLY;BYDAY=MO", "FREQ=MONTHLY"] {
+            assert_eq!(rule(value).next(end), None, "{}", value);
+            assert_eq!(rule(value).next_due(Some(end), end), None, "{}", value);
+        }
+    }
+}
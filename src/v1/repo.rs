@@ -1,9 +1,19 @@
-use crate::v1::entity::{Status, Story, Task};
+use crate::apikey::MintedKey;
+use crate::v1::entity::{
+    Access, ApiKey, BoardColumn, ChecklistItem, Label, LabelFilter, OwnerSummary, Placement,
+    Priority, Reminder, ReminderTrigger, Role, ShareLink, Status, StatusTarget, Story, StoryMember,
+    StoryScope, StoryUpdate, Task, TaskCounts, TaskDependencies, TaskFields, TaskSort, TaskUpdate,
+    Trash, Trashed, WipLimit, Workflow, WorkflowColumn,
+};
+use crate::v1::page::{Cursor, Page};
+use crate::v1::rank;
+use crate::v1::recurrence::Recurrence;
 use crate::{Error, Result};
 
+use chrono::{DateTime, Utc};
 use futures_util::TryStreamExt;
-use sqlx::postgres::{PgPool, PgRow};
-use sqlx::{FromRow, Row};
+use sqlx::postgres::{PgConnection, PgPool, PgRow};
+use sqlx::{FromRow, PgExecutor, Row};
 use std::sync::Arc;
 use uuid::Uuid;
 
@@ -11,6 +21,128 @@ pub struct Repo {
     db: Arc<PgPool>,
 }
 
+/// Columns selected for story entities; queries join `STORY_TASK_COUNTS` for the counts.
+const STORY_COLUMNS: &str = "id, name, owner, created_at, updated_at, \
+    counts.task_count, counts.completed_count, counts.overdue_count";
+
+/// Live task counts for each row of `stories`, aggregated in one pass per story.
+const STORY_TASK_COUNTS: &str = "LEFT JOIN LATERAL (\
+        SELECT count(*) AS task_count, \
+            count(*) FILTER (WHERE status = 'complete') AS completed_count, \
+            count(*) FILTER (WHERE due_at < now() AND status NOT IN ('complete', 'cancelled')) \
+                AS overdue_count \
+        FROM tasks WHERE story_id = stories.id AND deleted_at IS NULL\
+    ) counts ON true";
+
+/// Columns selected for task entities, qualified so queries can join `stories`.
+const TASK_COLUMNS: &str = "tasks.id, tasks.story_id, tasks.name, tasks.description, \
+    tasks.status, tasks.workflow_column, tasks.priority, tasks.rank, tasks.due_at, \
+    tasks.recurrence, tasks.created_at, tasks.updated_at, tasks.completed_at, \
+    ARRAY(SELECT label_id FROM task_labels WHERE task_id = tasks.id ORDER BY label_id) AS label_ids, \
+    (SELECT count(*) FROM checklist_items \
+        WHERE task_id = tasks.id AND deleted_at IS NULL) AS subtask_total, \
+    (SELECT count(*) FROM checklist_items \
+        WHERE task_id = tasks.id AND deleted_at IS NULL AND done) AS subtask_done";
+
+/// Columns selected for checklist item entities.
+const ITEM_COLUMNS: &str = "id, task_id, name, done, rank, created_at, updated_at";
+
+/// Columns selected for reminder entities, with the time each one fires.
+const REMINDER_COLUMNS: &str = "id, task_id, remind_at, offset_secs, created_at, sent_at, \
+    COALESCE(remind_at, \
+        (SELECT due_at FROM tasks WHERE id = reminders.task_id) - offset_secs * interval '1 second' \
+    ) AS fire_at";
+
+/// Columns selected for label entities.
+const LABEL_COLUMNS: &str = "id, owner, name, color, created_at";
+
+/// Columns selected for story member entities.
+const MEMBER_COLUMNS: &str = "story_id, member, role, created_at, updated_at";
+
+/// Columns selected for share link entities; the token hash never leaves the database.
+const SHARE_LINK_COLUMNS: &str = "id, story_id, created_by, created_at, expires_at, revoked_at, \
+    access_count, last_accessed_at";
+
+/// Columns selected for API key entities; the salt and hash never leave the database.
+const API_KEY_COLUMNS: &str = "id, owner, name, created_at, expires_at, revoked_at, last_used_at";
+
+/// Task filter for a `LabelFilter` bound as `$n` (label ids) and `$n+1` (match all).
+fn label_filter(n: usize) -> String {
+    format!(
+        r#"(cardinality(${n}::uuid[]) = 0 OR (
+                SELECT count(*) FROM task_labels
+                WHERE task_id = tasks.id AND label_id = ANY(${n})
+            ) >= CASE WHEN ${m} THEN cardinality(${n}) ELSE 1 END)"#,
+        m = n + 1
+    )
+}
+
+/// Filter for rows with a `story_id` in a story owned by `$n`.
+fn story_owner(n: usize) -> String {
+    format!("story_id IN (SELECT id FROM stories WHERE owner = ${n})")
+}
+
+/// Filter for rows with a `story_id` in a story owned by or shared with `$n`.
+fn story_member(n: usize) -> String {
+    format!(
+        "story_id IN (SELECT id FROM stories WHERE owner = ${n} \
+            UNION ALL SELECT story_id FROM story_members WHERE member = ${n})"
+    )
+}
+
+/// Filter for rows with a `story_id` in a live story owned by or shared with `$n`.
+fn live_story_member(n: usize) -> String {
+    format!(
+        "story_id IN (SELECT id FROM stories WHERE deleted_at IS NULL AND (owner = ${n} \
+            OR id IN (SELECT story_id FROM story_members WHERE member = ${n})))"
+    )
+}
+
+/// Query for the id of the story a scope belongs to, bound as `$n`.
+fn scope_story(scope: StoryScope, n: usize) -> String {
+    match scope {
+        StoryScope::Story(_) => format!("${n}"),
+        StoryScope::Task(_) => format!("SELECT story_id FROM tasks WHERE id = ${n}"),
+        StoryScope::ChecklistItem(_) => format!(
+            "SELECT t.story_id FROM checklist_items c JOIN tasks t ON t.id = c.task_id WHERE c.id = ${n}"
+        ),
+        StoryScope::Reminder(_) => format!(
+            "SELECT t.story_id FROM reminders r JOIN tasks t ON t.id = r.task_id WHERE r.id = ${n}"
+        ),
+        StoryScope::ShareLink(_) => format!("SELECT story_id FROM share_links WHERE id = ${n}"),
+    }
+}
+
+/// Filter for rows with a `task_id` of a task owned by `$n`.
+fn task_owner(n: usize) -> String {
+    format!("task_id IN (SELECT id FROM tasks WHERE {})", story_owner(n))
+}
+
+/// Longest rank kept before a story's ranks are respaced.
+const MAX_RANK_LEN: usize = 64;
+
+/// Rows ranked among the siblings that share a parent.
+struct RankScope {
+    table: &'static str,
+    parent: &'static str,
+}
+
+/// Tasks are ranked within their story.
+const TASK_RANKS: RankScope = RankScope {
+    table: "tasks",
+    parent: "story_id",
+};
+
+/// Checklist items are ranked within their task.
+const ITEM_RANKS: RankScope = RankScope {
+    table: "checklist_items",
+    parent: "task_id",
+};
+
+/// Sort key expression for the due date ordering, matching its index. Tasks
+/// without a due date sort last.
+const DUE_KEY: &str = "COALESCE(due_at, 'infinity')";
+
 impl Repo {
     /// Constructor
     pub fn new(db: Arc<PgPool>) -> Self {
@@ -19,14 +151,19 @@ impl Repo {
 
     /// Get a ref to the connection pool.
     pub fn db_ref(&self) -> &PgPool {
-        return self.db.as_ref();
+        self.db.as_ref()
     }
 }
 
 impl From<sqlx::Error> for Error {
     fn from(err: sqlx::Error) -> Self {
-        Error::InternalError {
-            message: err.to_string(),
+        match err {
+            sqlx::Error::RowNotFound => Error::NotFoundError {
+                message: err.to_string(),
+            },
+            _ => Error::InternalError {
+                message: err.to_string(),
+            },
         }
     }
 }
@@ -37,6 +174,13 @@ impl FromRow<'_, PgRow> for Story {
             story_id: row.try_get("id")?,
             name: row.try_get("name")?,
             owner: row.try_get("owner")?,
+            task_counts: TaskCounts {
+                total: row.try_get("task_count")?,
+                completed: row.try_get("completed_count")?,
+                overdue: row.try_get("overdue_count")?,
+            },
+            created_at: row.try_get("created_at")?,
+            updated_at: row.try_get("updated_at")?,
         })
     }
 }
@@ -47,54 +191,279 @@ impl FromRow<'_, PgRow> for Task {
         let task_id = row.try_get("id")?;
         let story_id = row.try_get("story_id")?;
         let name = row.try_get("name")?;
+        let description = row.try_get("description")?;
         let status: String = row.try_get("status")?;
+        let column = row.try_get("workflow_column")?;
+        let priority: i16 = row.try_get("priority")?;
+        let rank = row.try_get("rank")?;
+        let label_ids = row.try_get("label_ids")?;
+        let subtask_total = row.try_get("subtask_total")?;
+        let subtask_done = row.try_get("subtask_done")?;
+        let due_at = row.try_get("due_at")?;
+        let recurrence: Option<String> = row.try_get("recurrence")?;
+        let created_at = row.try_get("created_at")?;
+        let updated_at = row.try_get("updated_at")?;
+        let completed_at = row.try_get("completed_at")?;
 
         // Convert to enum type
         let status = Status::try_from(status)
             .map_err(|message| sqlx::Error::Decode(Box::new(Error::InternalError { message })))?;
+        let priority = Priority::try_from(priority)
+            .map_err(|message| sqlx::Error::Decode(Box::new(Error::InternalError { message })))?;
+        let recurrence = recurrence
+            .map(|rule| rule.parse())
+            .transpose()
+            .map_err(|message| sqlx::Error::Decode(Box::new(Error::InternalError { message })))?;
 
         // Task
         Ok(Self {
             task_id,
             story_id,
             name,
+            description,
             status,
+            column,
+            priority,
+            rank,
+            label_ids,
+            subtask_total,
+            subtask_done,
+            due_at,
+            recurrence,
+            created_at,
+            updated_at,
+            completed_at,
+        })
+    }
+}
+
+impl FromRow<'_, PgRow> for ChecklistItem {
+    fn from_row(row: &PgRow) -> std::result::Result<Self, sqlx::Error> {
+        Ok(Self {
+            item_id: row.try_get("id")?,
+            task_id: row.try_get("task_id")?,
+            name: row.try_get("name")?,
+            done: row.try_get("done")?,
+            rank: row.try_get("rank")?,
+            created_at: row.try_get("created_at")?,
+            updated_at: row.try_get("updated_at")?,
+        })
+    }
+}
+
+impl FromRow<'_, PgRow> for Reminder {
+    fn from_row(row: &PgRow) -> std::result::Result<Self, sqlx::Error> {
+        let remind_at: Option<DateTime<Utc>> = row.try_get("remind_at")?;
+        let offset_secs: Option<i64> = row.try_get("offset_secs")?;
+        let trigger = match (remind_at, offset_secs) {
+            (Some(at), None) => ReminderTrigger::At(at),
+            (None, Some(secs)) => ReminderTrigger::BeforeDue(chrono::Duration::seconds(secs)),
+            _ => {
+                return Err(sqlx::Error::Decode(Box::new(Error::InternalError {
+                    message: "reminder must have exactly one of remind_at or offset_secs"
+                        .to_string(),
+                })))
+            }
+        };
+
+        Ok(Self {
+            reminder_id: row.try_get("id")?,
+            task_id: row.try_get("task_id")?,
+            trigger,
+            fire_at: row.try_get("fire_at")?,
+            created_at: row.try_get("created_at")?,
+            sent_at: row.try_get("sent_at")?,
+        })
+    }
+}
+
+impl FromRow<'_, PgRow> for Label {
+    fn from_row(row: &PgRow) -> std::result::Result<Self, sqlx::Error> {
+        Ok(Self {
+            label_id: row.try_get("id")?,
+            owner: row.try_get("owner")?,
+            name: row.try_get("name")?,
+            color: row.try_get("color")?,
+            created_at: row.try_get("created_at")?,
+        })
+    }
+}
+
+impl FromRow<'_, PgRow> for StoryMember {
+    fn from_row(row: &PgRow) -> std::result::Result<Self, sqlx::Error> {
+        let role: String = row.try_get("role")?;
+        Ok(Self {
+            story_id: row.try_get("story_id")?,
+            member: row.try_get("member")?,
+            role: Role::try_from(role).map_err(|message| {
+                sqlx::Error::Decode(Box::new(Error::InternalError { message }))
+            })?,
+            created_at: row.try_get("created_at")?,
+            updated_at: row.try_get("updated_at")?,
+        })
+    }
+}
+
+impl FromRow<'_, PgRow> for ShareLink {
+    fn from_row(row: &PgRow) -> std::result::Result<Self, sqlx::Error> {
+        Ok(Self {
+            link_id: row.try_get("id")?,
+            story_id: row.try_get("story_id")?,
+            created_by: row.try_get("created_by")?,
+            created_at: row.try_get("created_at")?,
+            expires_at: row.try_get("expires_at")?,
+            revoked_at: row.try_get("revoked_at")?,
+            access_count: row.try_get("access_count")?,
+            last_accessed_at: row.try_get("last_accessed_at")?,
+        })
+    }
+}
+
+impl FromRow<'_, PgRow> for ApiKey {
+    fn from_row(row: &PgRow) -> std::result::Result<Self, sqlx::Error> {
+        Ok(Self {
+            key_id: row.try_get("id")?,
+            owner: row.try_get("owner")?,
+            name: row.try_get("name")?,
+            created_at: row.try_get("created_at")?,
+            expires_at: row.try_get("expires_at")?,
+            revoked_at: row.try_get("revoked_at")?,
+            last_used_at: row.try_get("last_used_at")?,
+        })
+    }
+}
+
+impl<T> FromRow<'_, PgRow> for Trashed<T>
+where
+    T: for<'r> FromRow<'r, PgRow>,
+{
+    fn from_row(row: &PgRow) -> std::result::Result<Self, sqlx::Error> {
+        Ok(Self {
+            item: T::from_row(row)?,
+            deleted_at: row.try_get("deleted_at")?,
         })
     }
 }
 
 impl Repo {
     /// Insert a new story
-    pub async fn insert_story(&self, name: String, owner: String) -> Result<Story> {
+    pub async fn insert_story(&self, name: String, owner: &str) -> Result<Story> {
         log::debug!("Repo::insert_story: {}, {}", &name, &owner);
 
-        let sql = r#"
-            INSERT INTO stories (name, owner)
-            VALUES ($1, $2)
-            RETURNING id, name, owner
-        "#;
+        let sql = format!(
+            r#"
+            WITH stories AS (
+                INSERT INTO stories (name, owner)
+                VALUES ($1, $2)
+                RETURNING *
+            )
+            SELECT {STORY_COLUMNS}
+            FROM stories {STORY_TASK_COUNTS}
+        "#
+        );
 
-        let story = sqlx::query_as(sql)
+        let story = sqlx::query_as(&sql)
             .bind(&name)
-            .bind(&owner)
+            .bind(owner)
             .fetch_one(self.db_ref())
             .await?;
 
         Ok(story)
     }
 
-    /// Select stories for an owner
-    pub async fn select_stories(&self, owner: String) -> Result<Vec<Story>> {
-        log::debug!("Repo::select_stories: {}", &owner);
+    /// Get an owner's story by id
+    pub async fn get_story(&self, owner: &str, story_id: Uuid) -> Result<Story> {
+        log::debug!("Repo::get_story: {}, {}", owner, &story_id);
 
-        let sql = r#"
-            SELECT id, name, owner
-            FROM stories
-            WHERE owner = $1 AND deleted_at IS NULL
-            ORDER BY created_at ASC
-        "#;
+        let sql = format!(
+            r#"
+            SELECT {STORY_COLUMNS}
+            FROM stories {STORY_TASK_COUNTS}
+            WHERE id = $1 AND owner = $2 AND deleted_at IS NULL
+        "#
+        );
+
+        let story = sqlx::query_as(&sql)
+            .bind(story_id)
+            .bind(owner)
+            .fetch_optional(self.db_ref())
+            .await?;
+
+        story.ok_or_else(|| Error::NotFoundError {
+            message: format!("story not found: {}", story_id),
+        })
+    }
+
+    /// Update story fields, leaving unset fields unchanged.
+    pub async fn update_story(
+        &self,
+        owner: &str,
+        story_id: Uuid,
+        update: StoryUpdate,
+    ) -> Result<Story> {
+        log::debug!(
+            "Repo::update_story: {}, {}, {:?}",
+            owner,
+            &story_id,
+            &update
+        );
+
+        let sql = format!(
+            r#"
+            WITH stories AS (
+                UPDATE stories SET name = COALESCE($2, name), updated_at = now()
+                WHERE id = $1 AND owner = $3 AND deleted_at IS NULL
+                RETURNING *
+            )
+            SELECT {STORY_COLUMNS}
+            FROM stories {STORY_TASK_COUNTS}
+        "#
+        );
+
+        let story = sqlx::query_as(&sql)
+            .bind(story_id)
+            .bind(&update.name)
+            .bind(owner)
+            .fetch_optional(self.db_ref())
+            .await?;
+
+        story.ok_or_else(|| Error::NotFoundError {
+            message: format!("story not found: {}", story_id),
+        })
+    }
+
+    /// Select a page of the stories a member owns or is a member of, ordered by creation time.
+    pub async fn select_stories(
+        &self,
+        member: &str,
+        cursor: Option<Cursor>,
+        limit: i64,
+    ) -> Result<Page<Story>> {
+        log::debug!(
+            "Repo::select_stories: {}, {:?}, {}",
+            &member,
+            &cursor,
+            limit
+        );
 
-        let mut result_set = sqlx::query(sql).bind(&owner).fetch(self.db_ref());
+        let sql = format!(
+            r#"
+            SELECT {STORY_COLUMNS}
+            FROM stories {STORY_TASK_COUNTS}
+            WHERE (owner = $1 OR id IN (SELECT story_id FROM story_members WHERE member = $1))
+            AND deleted_at IS NULL
+            AND ($2::timestamptz IS NULL OR (created_at, id) > ($2, $3))
+            ORDER BY created_at ASC, id ASC
+            LIMIT $4
+        "#
+        );
+
+        let mut result_set = sqlx::query(&sql)
+            .bind(member)
+            .bind(cursor.as_ref().map(|c| c.created_at))
+            .bind(cursor.as_ref().map(|c| c.id))
+            .bind(limit + 1)
+            .fetch(self.db_ref());
         let mut result = Vec::new();
 
         while let Some(row) = result_set.try_next().await? {
@@ -102,55 +471,199 @@ impl Repo {
             result.push(story);
         }
 
-        Ok(result)
+        Ok(Page::from_rows(result, limit, |s| {
+            Cursor::new(s.created_at, s.story_id)
+        }))
     }
 
-    /// Get a task by id
-    pub async fn get_task(&self, task_id: Uuid) -> Result<Task> {
-        log::debug!("Repo::get_task: {}", &task_id);
+    /// Sum live story and task counts across the stories a member owns or
+    /// that are shared with them.
+    pub async fn select_owner_summary(&self, member: &str) -> Result<OwnerSummary> {
+        log::debug!("Repo::select_owner_summary: {}", &member);
 
-        let sql =
-            "SELECT id, story_id, name, status FROM tasks WHERE id = $1 AND deleted_at IS NULL";
+        let sql = format!(
+            r#"
+            SELECT count(*) AS story_count,
+                COALESCE(sum(counts.task_count), 0)::bigint AS task_count,
+                COALESCE(sum(counts.completed_count), 0)::bigint AS completed_count,
+                COALESCE(sum(counts.overdue_count), 0)::bigint AS overdue_count
+            FROM stories {STORY_TASK_COUNTS}
+            WHERE (owner = $1 OR id IN (SELECT story_id FROM story_members WHERE member = $1))
+            AND deleted_at IS NULL
+        "#
+        );
 
-        let task = sqlx::query_as(sql)
-            .bind(&task_id)
+        let row = sqlx::query(&sql)
+            .bind(member)
             .fetch_one(self.db_ref())
             .await?;
 
-        Ok(task)
+        Ok(OwnerSummary {
+            story_count: row.try_get("story_count")?,
+            task_counts: TaskCounts {
+                total: row.try_get("task_count")?,
+                completed: row.try_get("completed_count")?,
+                overdue: row.try_get("overdue_count")?,
+            },
+            owner: member.to_string(),
+        })
     }
 
-    /// Insert a new story task
-    pub async fn insert_task(&self, story_id: Uuid, name: String) -> Result<Task> {
-        log::debug!("Repo::insert_task: {}, {}", &story_id, &name);
+    /// Get an owner's task by id
+    pub async fn get_task(&self, owner: &str, task_id: Uuid) -> Result<Task> {
+        log::debug!("Repo::get_task: {}, {}", owner, &task_id);
 
-        let sql = r#"
-            INSERT INTO tasks (story_id, name)
-            VALUES ($1, $2)
-            RETURNING id, story_id, name, status
-        "#;
+        let owned = story_owner(2);
+        let sql = format!(
+            r#"
+            SELECT {TASK_COLUMNS}
+            FROM tasks
+            WHERE id = $1 AND {owned} AND deleted_at IS NULL
+        "#
+        );
 
-        let task = sqlx::query_as(sql)
-            .bind(&story_id)
-            .bind(&name)
-            .fetch_one(self.db_ref())
+        let task = sqlx::query_as(&sql)
+            .bind(task_id)
+            .bind(owner)
+            .fetch_optional(self.db_ref())
+            .await?;
+
+        task.ok_or_else(|| Error::NotFoundError {
+            message: format!("task not found: {}", task_id),
+        })
+    }
+
+    /// Insert a new task into an owner's story
+    pub async fn insert_task(
+        &self,
+        owner: &str,
+        story_id: Uuid,
+        fields: TaskFields,
+    ) -> Result<Task> {
+        log::debug!("Repo::insert_task: {}, {}, {:?}", owner, &story_id, &fields);
+
+        let mut tx = self.db.begin().await?;
+
+        if !Self::lock_story(&mut tx, owner, story_id).await? {
+            return Err(Error::NotFoundError {
+                message: format!("story not found: {}", story_id),
+            });
+        }
+
+        let workflow = Self::select_workflow(&mut *tx, story_id).await?;
+        let column = workflow
+            .resolve(&StatusTarget::Status(Status::Incomplete))
+            .ok_or_else(|| Error::InternalError {
+                message: format!("story {} has no incomplete column", story_id),
+            })?;
+        Self::check_wip_limits(&mut tx, story_id, &workflow, None, None, column).await?;
+
+        // New tasks go to the end of the manual order.
+        let last = Self::select_last_rank(&mut tx, &TASK_RANKS, story_id).await?;
+
+        let sql = format!(
+            r#"
+            INSERT INTO tasks (story_id, name, description, priority, rank, due_at, recurrence)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            RETURNING {TASK_COLUMNS}
+        "#
+        );
+
+        let task = sqlx::query_as(&sql)
+            .bind(story_id)
+            .bind(&fields.name)
+            .bind(&fields.description)
+            .bind(i16::from(fields.priority.unwrap_or_default()))
+            .bind(rank::between(last.as_deref(), None))
+            .bind(fields.due_at)
+            .bind(fields.recurrence.map(|r| r.to_string()))
+            .fetch_one(&mut *tx)
             .await?;
 
+        tx.commit().await?;
+
         Ok(task)
     }
 
-    /// Select tasks for a story
-    pub async fn select_tasks(&self, story_id: Uuid) -> Result<Vec<Task>> {
-        log::debug!("Repo::select_tasks: story: {}", &story_id);
+    /// The highest rank among a parent's rows, including deleted rows so
+    /// that restored rows keep a unique position.
+    async fn select_last_rank(
+        tx: &mut PgConnection,
+        scope: &RankScope,
+        parent_id: Uuid,
+    ) -> Result<Option<String>> {
+        let RankScope { table, parent } = scope;
+        let sql = format!("SELECT max(rank) FROM {table} WHERE {parent} = $1");
+        let rank = sqlx::query_scalar(&sql)
+            .bind(parent_id)
+            .fetch_one(&mut *tx)
+            .await?;
+
+        Ok(rank)
+    }
+
+    /// Select a page of tasks for an owner's story in the given order.
+    pub async fn select_tasks(
+        &self,
+        owner: &str,
+        story_id: Uuid,
+        sort: TaskSort,
+        filter: &LabelFilter,
+        cursor: Option<Cursor>,
+        limit: i64,
+    ) -> Result<Page<Task>> {
+        log::debug!(
+            "Repo::select_tasks: {}, story: {}, {:?}, {:?}, {:?}, {}",
+            owner,
+            &story_id,
+            sort,
+            filter,
+            &cursor,
+            limit
+        );
 
-        let sql = r#"
-            SELECT id, story_id, name, status
+        // Leading sort expression and the type of its cursor key.
+        let (key, key_type) = match sort {
+            TaskSort::Created => (None, "text"),
+            TaskSort::Priority => (Some("priority"), "smallint"),
+            TaskSort::Due => (Some(DUE_KEY), "timestamptz"),
+            TaskSort::Rank => (Some("rank"), "text"),
+        };
+        let (order, after) = match key {
+            Some(key) => (
+                format!("{key} ASC, created_at ASC, id ASC"),
+                format!("({key}, created_at, id) > ($5::{key_type}, $2, $3)"),
+            ),
+            None => (
+                "created_at ASC, id ASC".to_string(),
+                "(created_at, id) > ($2, $3)".to_string(),
+            ),
+        };
+
+        let labels = label_filter(6);
+        let owned = story_owner(8);
+        let sql = format!(
+            r#"
+            SELECT {TASK_COLUMNS}
             FROM tasks
-            WHERE story_id = $1 AND deleted_at IS NULL
-            ORDER BY created_at ASC
-        "#;
+            WHERE story_id = $1 AND {owned} AND deleted_at IS NULL
+            AND ($2::timestamptz IS NULL OR {after})
+            AND {labels}
+            ORDER BY {order}
+            LIMIT $4
+        "#
+        );
 
-        let mut result_set = sqlx::query(sql).bind(&story_id).fetch(self.db_ref());
+        let mut result_set = sqlx::query(&sql)
+            .bind(story_id)
+            .bind(cursor.as_ref().map(|c| c.created_at))
+            .bind(cursor.as_ref().map(|c| c.id))
+            .bind(limit + 1)
+            .bind(cursor.as_ref().and_then(|c| c.key.as_deref()))
+            .bind(&filter.label_ids)
+            .bind(filter.match_all)
+            .bind(owner)
+            .fetch(self.db_ref());
         let mut result = Vec::new();
 
         while let Some(row) = result_set.try_next().await? {
@@ -158,62 +671,2019 @@ impl Repo {
             result.push(task);
         }
 
-        Ok(result)
+        Ok(Page::from_rows(result, limit, |t| {
+            Cursor::new(t.created_at, t.task_id).with_key(sort.key(t))
+        }))
     }
 
-    /// Update task status
-    pub async fn update_task_status(&self, task_id: Uuid, status: Status) -> Result<u64> {
-        log::debug!("Repo::update_task_status: {}, {}", &task_id, &status);
+    /// Update task fields, leaving unset fields unchanged.
+    pub async fn update_task(
+        &self,
+        owner: &str,
+        task_id: Uuid,
+        update: TaskUpdate,
+    ) -> Result<Task> {
+        log::debug!("Repo::update_task: {}, {}, {:?}", owner, &task_id, &update);
 
-        let status = status.to_string();
+        let owned = story_owner(9);
+        let sql = format!(
+            r#"
+            UPDATE tasks SET
+                name = COALESCE($2, name),
+                due_at = CASE WHEN $3 THEN $4 ELSE due_at END,
+                priority = COALESCE($5, priority),
+                description = COALESCE($6, description),
+                recurrence = CASE WHEN $7 THEN $8 ELSE recurrence END,
+                updated_at = now()
+            WHERE id = $1 AND {owned} AND deleted_at IS NULL
+            RETURNING {TASK_COLUMNS}
+        "#
+        );
 
-        let result = sqlx::query("UPDATE tasks SET status = $1, updated_at = now() WHERE id = $2")
-            .bind(&status)
-            .bind(&task_id)
-            .execute(self.db_ref())
+        let task = sqlx::query_as(&sql)
+            .bind(task_id)
+            .bind(&update.name)
+            .bind(update.due_at.is_some())
+            .bind(update.due_at.flatten())
+            .bind(update.priority.map(i16::from))
+            .bind(&update.description)
+            .bind(update.recurrence.is_some())
+            .bind(update.recurrence.flatten().map(|r| r.to_string()))
+            .bind(owner)
+            .fetch_optional(self.db_ref())
             .await?;
 
-        Ok(result.rows_affected())
+        task.ok_or_else(|| Error::NotFoundError {
+            message: format!("task not found: {}", task_id),
+        })
     }
 
-    /// Delete a story by setting the deleted_at timestamp.
-    pub async fn delete_story(&self, story_id: Uuid) -> Result<u64> {
-        log::debug!("Repo::delete_story: {}", &story_id);
+    /// Move a task to a column in its story's workflow, rejecting moves the
+    /// workflow does not allow.
+    pub async fn update_task_status(
+        &self,
+        owner: &str,
+        task_id: Uuid,
+        target: StatusTarget,
+    ) -> Result<u64> {
+        log::debug!(
+            "Repo::update_task_status: {}, {}, {}",
+            owner,
+            &task_id,
+            &target
+        );
 
         let mut tx = self.db.begin().await?;
 
-        let sql1 = r#"
-            UPDATE tasks SET deleted_at = now() WHERE story_id = $1
-            AND deleted_at IS NULL
+        // Lock the story before the task so moves within a story are serialized.
+        let story_id = Self::select_task_story(&mut tx, owner, task_id).await?;
+        let story_id = match story_id {
+            Some(story_id) if Self::lock_story(&mut tx, owner, story_id).await? => story_id,
+            _ => return Ok(0),
+        };
+
+        let sql = r#"
+            SELECT status, workflow_column FROM tasks
+            WHERE id = $1 AND story_id = $2 AND deleted_at IS NULL
+            FOR UPDATE
         "#;
-        let r1 = sqlx::query(sql1).bind(&story_id).execute(&mut *tx).await?;
+        let current: Option<(String, Option<String>)> = sqlx::query_as(sql)
+            .bind(task_id)
+            .bind(story_id)
+            .fetch_optional(&mut *tx)
+            .await?;
 
-        let sql2 = r#"
-            UPDATE stories SET deleted_at = now() WHERE id = $1
-            AND deleted_at IS NULL
+        let (current, current_column) = match current {
+            Some((current, column)) => (
+                Status::try_from(current).map_err(|message| Error::InternalError { message })?,
+                column,
+            ),
+            None => return Ok(0),
+        };
+
+        let workflow = Self::select_workflow(&mut *tx, story_id).await?;
+        let column = workflow
+            .resolve(&target)
+            .ok_or_else(|| Error::FailedPrecondition {
+                message: format!("{} is not in the workflow for story {}", target, story_id),
+            })?;
+
+        if !workflow.allows(current, column) {
+            return Err(Error::FailedPrecondition {
+                message: format!(
+                    "task {} cannot move from {} to {}",
+                    task_id, current, column.name
+                ),
+            });
+        }
+
+        if column.status == Status::Complete && current != Status::Complete {
+            Self::check_open_blockers(&mut tx, task_id).await?;
+        }
+
+        let from = Some((current, current_column.as_deref()));
+        Self::check_wip_limits(&mut tx, story_id, &workflow, Some(task_id), from, column).await?;
+
+        // Keep the original completion time when moving between done columns.
+        let sql = r#"
+            UPDATE tasks SET
+                status = $1,
+                workflow_column = $2,
+                completed_at = CASE WHEN $1 = 'complete' THEN COALESCE(completed_at, now()) END,
+                updated_at = now()
+            WHERE id = $3
         "#;
-        let r2 = sqlx::query(sql2).bind(&story_id).execute(&mut *tx).await?;
+        let result = sqlx::query(sql)
+            .bind(column.status.to_string())
+            .bind(workflow.custom.then_some(&column.name))
+            .bind(task_id)
+            .execute(&mut *tx)
+            .await?;
+
+        if column.status == Status::Complete && current != Status::Complete {
+            Self::insert_next_occurrence(&mut tx, story_id, task_id).await?;
+        }
 
         tx.commit().await?;
 
-        Ok(r1.rows_affected() + r2.rows_affected())
+        Ok(result.rows_affected())
     }
 
-    /// Delete a task by setting the deleted_at timestamp.
-    pub async fn delete_task(&self, task_id: Uuid) -> Result<u64> {
-        log::debug!("Repo::delete_task: {}", &task_id);
+    /// Create the next occurrence of a recurring task that was just completed.
+    /// The copy keeps the task's fields, labels, (unchecked) checklist and due
+    /// date reminders, and takes over the rule, so completing the old task
+    /// again does not repeat it. WIP limits are not checked: a full first
+    /// column must not stop a task from being completed.
+    async fn insert_next_occurrence(
+        tx: &mut PgConnection,
+        story_id: Uuid,
+        task_id: Uuid,
+    ) -> Result<()> {
+        let sql = "SELECT recurrence, due_at FROM tasks WHERE id = $1";
+        let (recurrence, due_at): (Option<String>, Option<DateTime<Utc>>) = sqlx::query_as(sql)
+            .bind(task_id)
+            .fetch_one(&mut *tx)
+            .await?;
+
+        let recurrence: Recurrence = match recurrence {
+            Some(rule) => rule
+                .parse()
+                .map_err(|message| Error::InternalError { message })?,
+            None => return Ok(()),
+        };
+
+        // Monthly rules keep the first due date's day from here on.
+        let now = Utc::now();
+        let recurrence = recurrence.anchored(due_at.unwrap_or(now));
 
+        let next_due = recurrence
+            .next_due(due_at, now)
+            .ok_or_else(|| Error::InvalidArgument {
+                message: format!("task {} has no next occurrence in range", task_id),
+            })?;
+
+        let last = Self::select_last_rank(&mut *tx, &TASK_RANKS, story_id).await?;
         let sql = r#"
-            UPDATE tasks SET deleted_at = now()
-            WHERE id = $1
-            AND deleted_at IS NULL
+            INSERT INTO tasks (story_id, name, description, priority, rank, due_at, recurrence)
+            SELECT story_id, name, description, priority, $2, $3, $4
+            FROM tasks WHERE id = $1
+            RETURNING id
         "#;
+        let next_id: Uuid = sqlx::query_scalar(sql)
+            .bind(task_id)
+            .bind(rank::between(last.as_deref(), None))
+            .bind(next_due)
+            .bind(recurrence.to_string())
+            .fetch_one(&mut *tx)
+            .await?;
 
-        let result = sqlx::query(sql)
-            .bind(&task_id)
-            .execute(self.db_ref())
+        let sql = r#"
+            INSERT INTO task_labels (task_id, label_id)
+            SELECT $2, label_id FROM task_labels WHERE task_id = $1
+        "#;
+        sqlx::query(sql)
+            .bind(task_id)
+            .bind(next_id)
+            .execute(&mut *tx)
             .await?;
 
-        Ok(result.rows_affected())
+        let sql = r#"
+            INSERT INTO checklist_items (task_id, name, rank)
+            SELECT $2, name, rank FROM checklist_items
+            WHERE task_id = $1 AND deleted_at IS NULL
+        "#;
+        sqlx::query(sql)
+            .bind(task_id)
+            .bind(next_id)
+            .execute(&mut *tx)
+            .await?;
+
+        // Reminders relative to the due date carry over; fixed-time ones don't.
+        let sql = r#"
+            INSERT INTO reminders (task_id, offset_secs)
+            SELECT $2, offset_secs FROM reminders
+            WHERE task_id = $1 AND offset_secs IS NOT NULL
+        "#;
+        sqlx::query(sql)
+            .bind(task_id)
+            .bind(next_id)
+            .execute(&mut *tx)
+            .await?;
+
+        sqlx::query("UPDATE tasks SET recurrence = NULL WHERE id = $1")
+            .bind(task_id)
+            .execute(&mut *tx)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Ensure none of a task's live blockers is still open.
+    async fn check_open_blockers(tx: &mut PgConnection, task_id: Uuid) -> Result<()> {
+        let sql = r#"
+            SELECT count(*) FROM tasks
+            WHERE id IN (SELECT blocker_id FROM task_dependencies WHERE task_id = $1)
+            AND deleted_at IS NULL AND status NOT IN ('complete', 'cancelled')
+        "#;
+        let open: i64 = sqlx::query_scalar(sql)
+            .bind(task_id)
+            .fetch_one(&mut *tx)
+            .await?;
+
+        if open > 0 {
+            return Err(Error::FailedPrecondition {
+                message: format!("task {} is blocked by {} open task(s)", task_id, open),
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Lock an owner's story row for the rest of the transaction, serializing
+    /// changes to its tasks. Returns false when the story does not exist, is
+    /// deleted or belongs to someone else.
+    async fn lock_story(tx: &mut PgConnection, owner: &str, story_id: Uuid) -> Result<bool> {
+        let sql = r#"
+            SELECT id FROM stories
+            WHERE id = $1 AND owner = $2 AND deleted_at IS NULL
+            FOR UPDATE
+        "#;
+        let story: Option<Uuid> = sqlx::query_scalar(sql)
+            .bind(story_id)
+            .bind(owner)
+            .fetch_optional(&mut *tx)
+            .await?;
+
+        Ok(story.is_some())
+    }
+
+    /// The story of an owner's live task, if there is one.
+    async fn select_task_story(
+        tx: &mut PgConnection,
+        owner: &str,
+        task_id: Uuid,
+    ) -> Result<Option<Uuid>> {
+        let owned = story_owner(2);
+        let sql =
+            format!("SELECT story_id FROM tasks WHERE id = $1 AND {owned} AND deleted_at IS NULL");
+        let story_id = sqlx::query_scalar(&sql)
+            .bind(task_id)
+            .bind(owner)
+            .fetch_optional(&mut *tx)
+            .await?;
+
+        Ok(story_id)
+    }
+
+    /// Ensure a task entering a column keeps the story within its WIP limits.
+    /// The caller must hold the story lock so concurrent moves cannot both pass.
+    async fn check_wip_limits(
+        tx: &mut PgConnection,
+        story_id: Uuid,
+        workflow: &Workflow,
+        task_id: Option<Uuid>,
+        from: Option<(Status, Option<&str>)>,
+        to: &WorkflowColumn,
+    ) -> Result<()> {
+        let limits = Self::select_wip_limits(&mut *tx, story_id).await?;
+        if limits.is_empty() {
+            return Ok(());
+        }
+
+        let sql = r#"
+            SELECT status, workflow_column FROM tasks
+            WHERE story_id = $1 AND deleted_at IS NULL
+            AND ($2::uuid IS NULL OR id <> $2)
+        "#;
+        let rows: Vec<(String, Option<String>)> = sqlx::query_as(sql)
+            .bind(story_id)
+            .bind(task_id)
+            .fetch_all(&mut *tx)
+            .await?;
+
+        let mut others = Vec::with_capacity(rows.len());
+        for (status, column) in rows {
+            let status =
+                Status::try_from(status).map_err(|message| Error::InternalError { message })?;
+            others.push((status, column));
+        }
+
+        workflow
+            .check_wip_limits(&limits, &others, from, to)
+            .map_err(|v| Error::WipLimitExceeded {
+                scope: v.scope,
+                limit: v.limit,
+                count: v.count,
+            })
+    }
+
+    /// Select the WIP limits for a story.
+    async fn select_wip_limits<'e, E>(executor: E, story_id: Uuid) -> Result<Vec<WipLimit>>
+    where
+        E: PgExecutor<'e>,
+    {
+        let sql = r#"
+            SELECT workflow_column, max_tasks FROM wip_limits
+            WHERE story_id = $1
+            ORDER BY workflow_column ASC NULLS FIRST
+        "#;
+
+        let rows: Vec<(Option<String>, i32)> = sqlx::query_as(sql)
+            .bind(story_id)
+            .fetch_all(executor)
+            .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|(column, max_tasks)| WipLimit {
+                column,
+                max_tasks: max_tasks as i64,
+            })
+            .collect())
+    }
+
+    /// Set or remove (when max_tasks is zero) a WIP limit for a story, returning
+    /// all of its limits.
+    pub async fn set_wip_limit(
+        &self,
+        owner: &str,
+        story_id: Uuid,
+        column: Option<String>,
+        max_tasks: i64,
+    ) -> Result<Vec<WipLimit>> {
+        log::debug!(
+            "Repo::set_wip_limit: {}, {}, {:?}, {}",
+            owner,
+            &story_id,
+            &column,
+            max_tasks
+        );
+
+        let mut tx = self.db.begin().await?;
+
+        if !Self::lock_story(&mut tx, owner, story_id).await? {
+            return Err(Error::NotFoundError {
+                message: format!("story not found: {}", story_id),
+            });
+        }
+
+        if let Some(name) = &column {
+            let workflow = Self::select_workflow(&mut *tx, story_id).await?;
+            if !workflow.columns.iter().any(|c| c.name == *name) {
+                return Err(Error::InvalidArgument {
+                    message: format!("{} is not in the workflow for story {}", name, story_id),
+                });
+            }
+        }
+
+        if max_tasks == 0 {
+            let sql = r#"
+                DELETE FROM wip_limits
+                WHERE story_id = $1 AND workflow_column IS NOT DISTINCT FROM $2
+            "#;
+            sqlx::query(sql)
+                .bind(story_id)
+                .bind(&column)
+                .execute(&mut *tx)
+                .await?;
+        } else {
+            let sql = r#"
+                INSERT INTO wip_limits (story_id, workflow_column, max_tasks)
+                VALUES ($1, $2, $3)
+                ON CONFLICT (story_id, COALESCE(workflow_column, ''))
+                DO UPDATE SET max_tasks = EXCLUDED.max_tasks, updated_at = now()
+            "#;
+            sqlx::query(sql)
+                .bind(story_id)
+                .bind(&column)
+                .bind(max_tasks as i32)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        let limits = Self::select_wip_limits(&mut *tx, story_id).await?;
+        tx.commit().await?;
+
+        Ok(limits)
+    }
+
+    /// Select the workflow for a story, falling back to the default columns.
+    async fn select_workflow<'e, E>(executor: E, story_id: Uuid) -> Result<Workflow>
+    where
+        E: PgExecutor<'e>,
+    {
+        let sql = r#"
+            SELECT name, status FROM workflow_columns
+            WHERE story_id = $1
+            ORDER BY position ASC
+        "#;
+
+        let rows: Vec<(String, String)> = sqlx::query_as(sql)
+            .bind(story_id)
+            .fetch_all(executor)
+            .await?;
+
+        if rows.is_empty() {
+            return Ok(Workflow::default());
+        }
+
+        let mut columns = Vec::with_capacity(rows.len());
+        for (name, status) in rows {
+            let status =
+                Status::try_from(status).map_err(|message| Error::InternalError { message })?;
+            columns.push(WorkflowColumn { name, status });
+        }
+
+        Ok(Workflow {
+            columns,
+            custom: true,
+        })
+    }
+
+    /// Replace the workflow for a story. Tasks left in removed columns fall back
+    /// to the first column for their status.
+    pub async fn replace_workflow(
+        &self,
+        owner: &str,
+        story_id: Uuid,
+        workflow: Workflow,
+    ) -> Result<Workflow> {
+        log::debug!(
+            "Repo::replace_workflow: {}, {}, {:?}",
+            owner,
+            &story_id,
+            &workflow
+        );
+
+        let mut tx = self.db.begin().await?;
+
+        if !Self::lock_story(&mut tx, owner, story_id).await? {
+            return Err(Error::NotFoundError {
+                message: format!("story not found: {}", story_id),
+            });
+        }
+
+        sqlx::query("DELETE FROM workflow_columns WHERE story_id = $1")
+            .bind(story_id)
+            .execute(&mut *tx)
+            .await?;
+
+        let names: Vec<String> = match workflow.custom {
+            true => workflow.columns.iter().map(|c| c.name.clone()).collect(),
+            false => Vec::new(),
+        };
+        if workflow.custom {
+            let statuses: Vec<String> = workflow
+                .columns
+                .iter()
+                .map(|c| c.status.to_string())
+                .collect();
+            let sql = r#"
+                INSERT INTO workflow_columns (story_id, name, status, position)
+                SELECT $1, c.name, c.status, c.position
+                FROM UNNEST($2::varchar[], $3::varchar[]) WITH ORDINALITY AS c(name, status, position)
+            "#;
+            sqlx::query(sql)
+                .bind(story_id)
+                .bind(&names)
+                .bind(&statuses)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        let sql = r#"
+            UPDATE tasks SET workflow_column = NULL
+            WHERE story_id = $1 AND workflow_column IS NOT NULL
+            AND NOT (workflow_column = ANY($2))
+        "#;
+        sqlx::query(sql)
+            .bind(story_id)
+            .bind(&names)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+
+        Ok(workflow)
+    }
+
+    /// Select the board for an owner's story: its workflow columns and the
+    /// tasks in each, in manual order.
+    pub async fn select_board(&self, owner: &str, story_id: Uuid) -> Result<Vec<BoardColumn>> {
+        log::debug!("Repo::select_board: {}, {}", owner, &story_id);
+
+        self.get_story(owner, story_id).await?;
+        let workflow = Self::select_workflow(self.db_ref(), story_id).await?;
+
+        let sql = format!(
+            r#"
+            SELECT {TASK_COLUMNS}
+            FROM tasks
+            WHERE story_id = $1 AND deleted_at IS NULL
+            ORDER BY rank ASC, created_at ASC, id ASC
+        "#
+        );
+
+        let tasks = sqlx::query_as(&sql)
+            .bind(story_id)
+            .fetch_all(self.db_ref())
+            .await?;
+
+        Ok(workflow.board(tasks))
+    }
+
+    /// Delete an owner's story by setting the deleted_at timestamp. The story
+    /// and the tasks deleted with it share a deletion id so they can be
+    /// restored together.
+    pub async fn delete_story(&self, owner: &str, story_id: Uuid) -> Result<u64> {
+        log::debug!("Repo::delete_story: {}, {}", owner, &story_id);
+
+        let deletion_id = Uuid::new_v4();
+        let mut tx = self.db.begin().await?;
+
+        let sql1 = r#"
+            UPDATE stories SET deleted_at = now(), deletion_id = $2 WHERE id = $1
+            AND owner = $3 AND deleted_at IS NULL
+        "#;
+        let r1 = sqlx::query(sql1)
+            .bind(story_id)
+            .bind(deletion_id)
+            .bind(owner)
+            .execute(&mut *tx)
+            .await?;
+
+        // Tasks are only deleted along with a story the caller could delete.
+        if r1.rows_affected() == 0 {
+            return Ok(0);
+        }
+
+        let sql2 = r#"
+            UPDATE tasks SET deleted_at = now(), deletion_id = $2 WHERE story_id = $1
+            AND deleted_at IS NULL
+            RETURNING id
+        "#;
+        let task_ids: Vec<Uuid> = sqlx::query_scalar(sql2)
+            .bind(story_id)
+            .bind(deletion_id)
+            .fetch_all(&mut *tx)
+            .await?;
+
+        Self::delete_checklist_items(&mut tx, &task_ids, deletion_id).await?;
+
+        tx.commit().await?;
+
+        Ok(r1.rows_affected() + task_ids.len() as u64)
+    }
+
+    /// Soft-delete the live checklist items of tasks, in the tasks' deletion batch.
+    async fn delete_checklist_items(
+        tx: &mut PgConnection,
+        task_ids: &[Uuid],
+        deletion_id: Uuid,
+    ) -> Result<()> {
+        let sql = r#"
+            UPDATE checklist_items SET deleted_at = now(), deletion_id = $2
+            WHERE task_id = ANY($1)
+            AND deleted_at IS NULL
+        "#;
+        sqlx::query(sql)
+            .bind(task_ids)
+            .bind(deletion_id)
+            .execute(&mut *tx)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Restore the checklist items deleted in a batch.
+    async fn restore_checklist_items(tx: &mut PgConnection, deletion_id: Uuid) -> Result<()> {
+        let sql = r#"
+            UPDATE checklist_items SET deleted_at = NULL, deletion_id = NULL, updated_at = now()
+            WHERE deletion_id = $1
+        "#;
+        sqlx::query(sql).bind(deletion_id).execute(&mut *tx).await?;
+
+        Ok(())
+    }
+
+    /// Move a task before or after a sibling in its story's manual order.
+    /// Only the moved task is re-ranked, unless ranks have grown too long.
+    pub async fn update_task_rank(
+        &self,
+        owner: &str,
+        task_id: Uuid,
+        placement: Placement,
+    ) -> Result<u64> {
+        log::debug!(
+            "Repo::update_task_rank: {}, {}, {:?}",
+            owner,
+            &task_id,
+            &placement
+        );
+
+        let mut tx = self.db.begin().await?;
+
+        // Lock the story before the task so moves within a story are serialized.
+        let story_id = Self::select_task_story(&mut tx, owner, task_id).await?;
+        let story_id = match story_id {
+            Some(story_id) if Self::lock_story(&mut tx, owner, story_id).await? => story_id,
+            _ => return Ok(0),
+        };
+
+        let sibling = placement.sibling();
+        let sql = "SELECT story_id FROM tasks WHERE id = $1 AND deleted_at IS NULL";
+        let sibling_story: Option<Uuid> = sqlx::query_scalar(sql)
+            .bind(sibling)
+            .fetch_optional(&mut *tx)
+            .await?;
+
+        match sibling_story {
+            None => {
+                return Err(Error::NotFoundError {
+                    message: format!("task not found: {}", sibling),
+                })
+            }
+            Some(id) if id != story_id => {
+                return Err(Error::FailedPrecondition {
+                    message: format!("task {} is not in story {}", sibling, story_id),
+                })
+            }
+            _ => {}
+        }
+
+        let mut rank = Self::rank_at(&mut tx, &TASK_RANKS, story_id, task_id, placement).await?;
+        if rank.len() > MAX_RANK_LEN {
+            Self::respace_ranks(&mut tx, &TASK_RANKS, story_id).await?;
+            rank = Self::rank_at(&mut tx, &TASK_RANKS, story_id, task_id, placement).await?;
+        }
+
+        let sql = "UPDATE tasks SET rank = $2, updated_at = now() WHERE id = $1";
+        let result = sqlx::query(sql)
+            .bind(task_id)
+            .bind(&rank)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Move an owner's task to the end of another of their stories. The task
+    /// keeps its status and lands in the target's first column for it.
+    pub async fn update_task_story(
+        &self,
+        owner: &str,
+        task_id: Uuid,
+        story_id: Uuid,
+    ) -> Result<u64> {
+        log::debug!(
+            "Repo::update_task_story: {}, {}, {}",
+            owner,
+            &task_id,
+            &story_id
+        );
+
+        let mut tx = self.db.begin().await?;
+
+        let source = match Self::select_task_story(&mut tx, owner, task_id).await? {
+            Some(source) => source,
+            None => return Ok(0),
+        };
+
+        // Lock both stories in id order so opposing moves cannot deadlock.
+        let sql = r#"
+            SELECT id FROM stories
+            WHERE id = ANY($1) AND owner = $2 AND deleted_at IS NULL
+            ORDER BY id
+            FOR UPDATE
+        "#;
+        let locked: Vec<Uuid> = sqlx::query_scalar(sql)
+            .bind(vec![source, story_id])
+            .bind(owner)
+            .fetch_all(&mut *tx)
+            .await?;
+
+        if !locked.contains(&source) {
+            return Ok(0);
+        }
+        if !locked.contains(&story_id) {
+            return Err(Error::NotFoundError {
+                message: format!("story not found: {}", story_id),
+            });
+        }
+
+        // The task may have been deleted or moved before the stories were locked.
+        let sql = r#"
+            SELECT status FROM tasks
+            WHERE id = $1 AND story_id = $2 AND deleted_at IS NULL
+            FOR UPDATE
+        "#;
+        let status: Option<String> = sqlx::query_scalar(sql)
+            .bind(task_id)
+            .bind(source)
+            .fetch_optional(&mut *tx)
+            .await?;
+        let status = match status {
+            Some(status) => {
+                Status::try_from(status).map_err(|message| Error::InternalError { message })?
+            }
+            None => return Ok(0),
+        };
+
+        if source == story_id {
+            return Ok(1);
+        }
+
+        let workflow = Self::select_workflow(&mut *tx, story_id).await?;
+        let column = workflow
+            .resolve(&StatusTarget::Status(status))
+            .ok_or_else(|| Error::FailedPrecondition {
+                message: format!("story {} has no {} column", story_id, status),
+            })?;
+        Self::check_wip_limits(&mut tx, story_id, &workflow, None, None, column).await?;
+
+        let last = Self::select_last_rank(&mut tx, &TASK_RANKS, story_id).await?;
+
+        let sql = r#"
+            UPDATE tasks SET story_id = $2, workflow_column = $3, rank = $4, updated_at = now()
+            WHERE id = $1
+        "#;
+        let result = sqlx::query(sql)
+            .bind(task_id)
+            .bind(story_id)
+            .bind(workflow.custom.then_some(&column.name))
+            .bind(rank::between(last.as_deref(), None))
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// A new rank for a row placed next to a sibling, between the sibling and
+    /// its neighbour on that side (ignoring the row being moved).
+    async fn rank_at(
+        tx: &mut PgConnection,
+        scope: &RankScope,
+        parent_id: Uuid,
+        id: Uuid,
+        placement: Placement,
+    ) -> Result<String> {
+        let RankScope { table, parent } = scope;
+        let sql = format!(
+            r#"
+            SELECT
+                s.rank,
+                (SELECT max(rank) FROM {table} t
+                 WHERE t.{parent} = $1 AND t.deleted_at IS NULL AND t.id <> $2 AND t.rank < s.rank),
+                (SELECT min(rank) FROM {table} t
+                 WHERE t.{parent} = $1 AND t.deleted_at IS NULL AND t.id <> $2 AND t.rank > s.rank)
+            FROM {table} s
+            WHERE s.id = $3
+        "#
+        );
+        let (rank, prev, next): (String, Option<String>, Option<String>) = sqlx::query_as(&sql)
+            .bind(parent_id)
+            .bind(id)
+            .bind(placement.sibling())
+            .fetch_one(&mut *tx)
+            .await?;
+
+        let rank = match placement {
+            Placement::Before(_) => rank::between(prev.as_deref(), Some(&rank)),
+            Placement::After(_) => rank::between(Some(&rank), next.as_deref()),
+        };
+
+        Ok(rank)
+    }
+
+    /// Evenly respace a parent's ranks, keeping the current order.
+    async fn respace_ranks(
+        tx: &mut PgConnection,
+        scope: &RankScope,
+        parent_id: Uuid,
+    ) -> Result<()> {
+        let RankScope { table, parent } = scope;
+        log::info!("Respacing {} ranks for {} {}", table, parent, parent_id);
+
+        let sql = format!(
+            r#"
+            UPDATE {table} SET rank = ranked.rank
+            FROM (
+                SELECT id, lpad(row_number() OVER (ORDER BY rank, created_at, id)::text, 8, '0') || 'i' AS rank
+                FROM {table}
+                WHERE {parent} = $1
+            ) ranked
+            WHERE {table}.id = ranked.id
+        "#
+        );
+        sqlx::query(&sql).bind(parent_id).execute(&mut *tx).await?;
+
+        Ok(())
+    }
+
+    /// Delete an owner's task and its checklist items by setting the
+    /// deleted_at timestamp.
+    pub async fn delete_task(&self, owner: &str, task_id: Uuid) -> Result<u64> {
+        log::debug!("Repo::delete_task: {}, {}", owner, &task_id);
+
+        let deletion_id = Uuid::new_v4();
+        let mut tx = self.db.begin().await?;
+
+        let owned = story_owner(3);
+        let sql = format!(
+            r#"
+            UPDATE tasks SET deleted_at = now(), deletion_id = $2
+            WHERE id = $1 AND {owned}
+            AND deleted_at IS NULL
+        "#
+        );
+
+        let result = sqlx::query(&sql)
+            .bind(task_id)
+            .bind(deletion_id)
+            .bind(owner)
+            .execute(&mut *tx)
+            .await?;
+
+        if result.rows_affected() > 0 {
+            Self::delete_checklist_items(&mut tx, &[task_id], deletion_id).await?;
+        }
+
+        tx.commit().await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Restore a deleted story along with the tasks deleted in the same batch.
+    /// Tasks deleted on their own before the story stay deleted.
+    pub async fn restore_story(&self, owner: &str, story_id: Uuid) -> Result<u64> {
+        log::debug!("Repo::restore_story: {}, {}", owner, &story_id);
+
+        let mut tx = self.db.begin().await?;
+
+        let sql = r#"
+            SELECT deletion_id, deleted_at FROM stories
+            WHERE id = $1 AND owner = $2 AND deleted_at IS NOT NULL
+            FOR UPDATE
+        "#;
+        let deleted: Option<(Option<Uuid>, DateTime<Utc>)> = sqlx::query_as(sql)
+            .bind(story_id)
+            .bind(owner)
+            .fetch_optional(&mut *tx)
+            .await?;
+
+        let (deletion_id, deleted_at) = match deleted {
+            Some(deleted) => deleted,
+            None => return Ok(0),
+        };
+
+        let sql1 = r#"
+            UPDATE stories SET deleted_at = NULL, deletion_id = NULL, updated_at = now()
+            WHERE id = $1
+        "#;
+        let r1 = sqlx::query(sql1).bind(story_id).execute(&mut *tx).await?;
+
+        // Rows deleted before deletion ids existed match on the shared
+        // transaction timestamp instead.
+        let sql2 = r#"
+            UPDATE tasks SET deleted_at = NULL, deletion_id = NULL, updated_at = now()
+            WHERE story_id = $1 AND deleted_at IS NOT NULL
+            AND (deletion_id = $2 OR ($2 IS NULL AND deletion_id IS NULL AND deleted_at = $3))
+        "#;
+        let r2 = sqlx::query(sql2)
+            .bind(story_id)
+            .bind(deletion_id)
+            .bind(deleted_at)
+            .execute(&mut *tx)
+            .await?;
+
+        if let Some(deletion_id) = deletion_id {
+            Self::restore_checklist_items(&mut tx, deletion_id).await?;
+        }
+
+        tx.commit().await?;
+
+        Ok(r1.rows_affected() + r2.rows_affected())
+    }
+
+    /// Restore a deleted task. Its story must not be deleted, and the task must
+    /// fit within the story's WIP limits.
+    pub async fn restore_task(&self, owner: &str, task_id: Uuid) -> Result<u64> {
+        log::debug!("Repo::restore_task: {}, {}", owner, &task_id);
+
+        let mut tx = self.db.begin().await?;
+
+        let owned = story_owner(2);
+        let sql = format!(
+            "SELECT story_id FROM tasks WHERE id = $1 AND {owned} AND deleted_at IS NOT NULL"
+        );
+        let story_id: Option<Uuid> = sqlx::query_scalar(&sql)
+            .bind(task_id)
+            .bind(owner)
+            .fetch_optional(&mut *tx)
+            .await?;
+
+        let story_id = match story_id {
+            Some(story_id) => story_id,
+            None => return Ok(0),
+        };
+
+        if !Self::lock_story(&mut tx, owner, story_id).await? {
+            return Err(Error::FailedPrecondition {
+                message: format!("story {} is deleted; restore the story first", story_id),
+            });
+        }
+
+        let sql = r#"
+            SELECT status, workflow_column, deletion_id FROM tasks
+            WHERE id = $1 AND story_id = $2 AND deleted_at IS NOT NULL
+            FOR UPDATE
+        "#;
+        let deleted: Option<(String, Option<String>, Option<Uuid>)> = sqlx::query_as(sql)
+            .bind(task_id)
+            .bind(story_id)
+            .fetch_optional(&mut *tx)
+            .await?;
+
+        let (status, column, deletion_id) = match deleted {
+            Some((status, column, deletion_id)) => (
+                Status::try_from(status).map_err(|message| Error::InternalError { message })?,
+                column,
+                deletion_id,
+            ),
+            None => return Ok(0),
+        };
+
+        let workflow = Self::select_workflow(&mut *tx, story_id).await?;
+        let to = &workflow.columns[workflow.column_index(status, column.as_deref())];
+        let to = WorkflowColumn {
+            name: to.name.clone(),
+            status,
+        };
+        Self::check_wip_limits(&mut tx, story_id, &workflow, Some(task_id), None, &to).await?;
+
+        let sql = r#"
+            UPDATE tasks SET deleted_at = NULL, deletion_id = NULL, updated_at = now()
+            WHERE id = $1
+        "#;
+        let result = sqlx::query(sql).bind(task_id).execute(&mut *tx).await?;
+
+        if let Some(deletion_id) = deletion_id {
+            Self::restore_checklist_items(&mut tx, deletion_id).await?;
+        }
+
+        tx.commit().await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Select an owner's deleted stories, and tasks deleted on their own from
+    /// live stories, most recently deleted first.
+    pub async fn select_trash(&self, owner: &str) -> Result<Trash> {
+        log::debug!("Repo::select_trash: {}", &owner);
+
+        let sql1 = format!(
+            r#"
+            SELECT {STORY_COLUMNS}, deleted_at
+            FROM stories {STORY_TASK_COUNTS}
+            WHERE owner = $1 AND deleted_at IS NOT NULL
+            ORDER BY deleted_at DESC, id ASC
+        "#
+        );
+        let stories = sqlx::query_as(&sql1)
+            .bind(owner)
+            .fetch_all(self.db_ref())
+            .await?;
+
+        let sql2 = format!(
+            r#"
+            SELECT {TASK_COLUMNS}, tasks.deleted_at
+            FROM tasks JOIN stories s ON s.id = tasks.story_id
+            WHERE s.owner = $1 AND s.deleted_at IS NULL AND tasks.deleted_at IS NOT NULL
+            ORDER BY tasks.deleted_at DESC, tasks.id ASC
+        "#
+        );
+        let tasks = sqlx::query_as(&sql2)
+            .bind(owner)
+            .fetch_all(self.db_ref())
+            .await?;
+
+        Ok(Trash { stories, tasks })
+    }
+
+    /// Select a page of the open tasks due within a window (either bound may
+    /// be open), optionally including overdue tasks, across the live stories a
+    /// member owns or that are shared with them, ordered by due date.
+    pub async fn select_due_tasks(
+        &self,
+        member: &str,
+        due_after: Option<DateTime<Utc>>,
+        due_before: Option<DateTime<Utc>>,
+        include_overdue: bool,
+        cursor: Option<Cursor>,
+        limit: i64,
+    ) -> Result<Page<Task>> {
+        log::debug!(
+            "Repo::select_due_tasks: {}, {:?}, {:?}, {}, {:?}, {}",
+            &member,
+            &due_after,
+            &due_before,
+            include_overdue,
+            &cursor,
+            limit
+        );
+
+        let stories = live_story_member(1);
+        let sql = format!(
+            r#"
+            SELECT {TASK_COLUMNS}
+            FROM tasks
+            WHERE {stories}
+            AND deleted_at IS NULL AND due_at IS NOT NULL
+            AND status NOT IN ('complete', 'cancelled')
+            AND (
+                (($2::timestamptz IS NULL OR due_at >= $2) AND ($3::timestamptz IS NULL OR due_at < $3))
+                OR ($4 AND due_at < now())
+            )
+            AND ($5::text IS NULL OR (due_at, id) > ($5::timestamptz, $6))
+            ORDER BY due_at ASC, id ASC
+            LIMIT $7
+        "#
+        );
+
+        let tasks = sqlx::query_as(&sql)
+            .bind(member)
+            .bind(due_after)
+            .bind(due_before)
+            .bind(include_overdue)
+            .bind(cursor.as_ref().and_then(|c| c.key.as_deref()))
+            .bind(cursor.as_ref().map(|c| c.id))
+            .bind(limit + 1)
+            .fetch_all(self.db_ref())
+            .await?;
+
+        Ok(Page::from_rows(tasks, limit, |t| {
+            Cursor::new(t.created_at, t.task_id).with_key(TaskSort::Due.key(t))
+        }))
+    }
+
+    /// Select a page of labeled tasks across the live stories a member owns or
+    /// that are shared with them, ordered by creation time.
+    pub async fn select_labeled_tasks(
+        &self,
+        member: &str,
+        filter: &LabelFilter,
+        cursor: Option<Cursor>,
+        limit: i64,
+    ) -> Result<Page<Task>> {
+        log::debug!(
+            "Repo::select_labeled_tasks: {}, {:?}, {:?}, {}",
+            &member,
+            filter,
+            &cursor,
+            limit
+        );
+
+        let labels = label_filter(5);
+        let stories = live_story_member(1);
+        let sql = format!(
+            r#"
+            SELECT {TASK_COLUMNS}
+            FROM tasks
+            WHERE {stories}
+            AND deleted_at IS NULL
+            AND ($2::timestamptz IS NULL OR (created_at, id) > ($2, $3))
+            AND {labels}
+            ORDER BY created_at ASC, id ASC
+            LIMIT $4
+        "#
+        );
+
+        let tasks = sqlx::query_as(&sql)
+            .bind(member)
+            .bind(cursor.as_ref().map(|c| c.created_at))
+            .bind(cursor.as_ref().map(|c| c.id))
+            .bind(limit + 1)
+            .bind(&filter.label_ids)
+            .bind(filter.match_all)
+            .fetch_all(self.db_ref())
+            .await?;
+
+        Ok(Page::from_rows(tasks, limit, |t| {
+            Cursor::new(t.created_at, t.task_id)
+        }))
+    }
+
+    /// Insert a label for an owner. Returns `None` when the owner already has
+    /// a label with the same name, ignoring case.
+    pub async fn insert_label(
+        &self,
+        owner: &str,
+        name: String,
+        color: String,
+    ) -> Result<Option<Label>> {
+        log::debug!("Repo::insert_label: {}, {}, {}", &owner, &name, &color);
+
+        let sql = format!(
+            r#"
+            INSERT INTO labels (owner, name, color)
+            VALUES ($1, $2, $3)
+            ON CONFLICT DO NOTHING
+            RETURNING {LABEL_COLUMNS}
+        "#
+        );
+
+        let label = sqlx::query_as(&sql)
+            .bind(owner)
+            .bind(&name)
+            .bind(&color)
+            .fetch_optional(self.db_ref())
+            .await?;
+
+        Ok(label)
+    }
+
+    /// Select an owner's labels, ordered by name.
+    pub async fn select_labels(&self, owner: &str) -> Result<Vec<Label>> {
+        log::debug!("Repo::select_labels: {}", &owner);
+
+        let sql = format!(
+            r#"
+            SELECT {LABEL_COLUMNS}
+            FROM labels
+            WHERE owner = $1
+            ORDER BY lower(name) ASC, id ASC
+        "#
+        );
+
+        let labels = sqlx::query_as(&sql)
+            .bind(owner)
+            .fetch_all(self.db_ref())
+            .await?;
+
+        Ok(labels)
+    }
+
+    /// Delete an owner's label, detaching it from every task.
+    pub async fn delete_label(&self, owner: &str, label_id: Uuid) -> Result<u64> {
+        log::debug!("Repo::delete_label: {}, {}", owner, &label_id);
+
+        let sql = "DELETE FROM labels WHERE id = $1 AND owner = $2";
+        let result = sqlx::query(sql)
+            .bind(label_id)
+            .bind(owner)
+            .execute(self.db_ref())
+            .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Attach an owner's label to one of their live tasks. Attaching an
+    /// attached label is a no-op.
+    pub async fn insert_task_label(
+        &self,
+        owner: &str,
+        task_id: Uuid,
+        label_id: Uuid,
+    ) -> Result<()> {
+        log::debug!(
+            "Repo::insert_task_label: {}, {}, {}",
+            owner,
+            &task_id,
+            &label_id
+        );
+
+        let sql = r#"
+            SELECT l.id
+            FROM tasks t
+            JOIN stories s ON s.id = t.story_id AND s.deleted_at IS NULL
+            LEFT JOIN labels l ON l.id = $2 AND l.owner = s.owner
+            WHERE t.id = $1 AND s.owner = $3 AND t.deleted_at IS NULL
+        "#;
+        let label: Option<Option<Uuid>> = sqlx::query_scalar(sql)
+            .bind(task_id)
+            .bind(label_id)
+            .bind(owner)
+            .fetch_optional(self.db_ref())
+            .await?;
+
+        match label {
+            None => {
+                return Err(Error::NotFoundError {
+                    message: format!("task not found: {}", task_id),
+                })
+            }
+            Some(None) => {
+                return Err(Error::NotFoundError {
+                    message: format!("label not found: {}", label_id),
+                })
+            }
+            _ => {}
+        }
+
+        let sql = r#"
+            INSERT INTO task_labels (task_id, label_id)
+            VALUES ($1, $2)
+            ON CONFLICT DO NOTHING
+        "#;
+        sqlx::query(sql)
+            .bind(task_id)
+            .bind(label_id)
+            .execute(self.db_ref())
+            .await?;
+
+        Ok(())
+    }
+
+    /// Detach a label from an owner's task.
+    pub async fn delete_task_label(
+        &self,
+        owner: &str,
+        task_id: Uuid,
+        label_id: Uuid,
+    ) -> Result<u64> {
+        log::debug!(
+            "Repo::delete_task_label: {}, {}, {}",
+            owner,
+            &task_id,
+            &label_id
+        );
+
+        let owned = task_owner(3);
+        let sql =
+            format!("DELETE FROM task_labels WHERE task_id = $1 AND label_id = $2 AND {owned}");
+        let result = sqlx::query(&sql)
+            .bind(task_id)
+            .bind(label_id)
+            .bind(owner)
+            .execute(self.db_ref())
+            .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Record that an owner's task is blocked by another of their tasks,
+    /// rejecting dependencies that would form a cycle.
+    pub async fn insert_task_dependency(
+        &self,
+        owner: &str,
+        task_id: Uuid,
+        blocker_id: Uuid,
+    ) -> Result<()> {
+        log::debug!(
+            "Repo::insert_task_dependency: {}, {}, {}",
+            owner,
+            &task_id,
+            &blocker_id
+        );
+
+        let mut tx = self.db.begin().await?;
+
+        let sql = r#"
+            SELECT t.id
+            FROM tasks t
+            JOIN stories s ON s.id = t.story_id AND s.deleted_at IS NULL
+            WHERE t.id IN ($1, $2) AND s.owner = $3 AND t.deleted_at IS NULL
+        "#;
+        let found: Vec<Uuid> = sqlx::query_scalar(sql)
+            .bind(task_id)
+            .bind(blocker_id)
+            .bind(owner)
+            .fetch_all(&mut *tx)
+            .await?;
+
+        for id in [task_id, blocker_id] {
+            if !found.contains(&id) {
+                return Err(Error::NotFoundError {
+                    message: format!("task not found: {}", id),
+                });
+            }
+        }
+
+        // Dependencies never cross owners, so serializing per owner is enough
+        // to stop two concurrent inserts from closing a cycle together.
+        sqlx::query("SELECT pg_advisory_xact_lock(hashtext('task_dependencies:' || $1))")
+            .bind(owner)
+            .execute(&mut *tx)
+            .await?;
+
+        // Walk everything the blocker waits on, including deleted tasks that
+        // could later be restored; reaching the task means a cycle.
+        let sql = r#"
+            WITH RECURSIVE blockers(id) AS (
+                SELECT blocker_id FROM task_dependencies WHERE task_id = $1
+                UNION
+                SELECT d.blocker_id
+                FROM task_dependencies d
+                JOIN blockers b ON d.task_id = b.id
+            )
+            SELECT EXISTS (SELECT 1 FROM blockers WHERE id = $2)
+        "#;
+        let cycle: bool = sqlx::query_scalar(sql)
+            .bind(blocker_id)
+            .bind(task_id)
+            .fetch_one(&mut *tx)
+            .await?;
+
+        if cycle {
+            return Err(Error::FailedPrecondition {
+                message: format!(
+                    "task {} already depends on task {}; the dependency would form a cycle",
+                    blocker_id, task_id
+                ),
+            });
+        }
+
+        let sql = r#"
+            INSERT INTO task_dependencies (task_id, blocker_id)
+            VALUES ($1, $2)
+            ON CONFLICT DO NOTHING
+        "#;
+        sqlx::query(sql)
+            .bind(task_id)
+            .bind(blocker_id)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    /// Remove a dependency between two of an owner's tasks.
+    pub async fn delete_task_dependency(
+        &self,
+        owner: &str,
+        task_id: Uuid,
+        blocker_id: Uuid,
+    ) -> Result<u64> {
+        log::debug!(
+            "Repo::delete_task_dependency: {}, {}, {}",
+            owner,
+            &task_id,
+            &blocker_id
+        );
+
+        let owned = task_owner(3);
+        let sql = format!(
+            "DELETE FROM task_dependencies WHERE task_id = $1 AND blocker_id = $2 AND {owned}"
+        );
+        let result = sqlx::query(&sql)
+            .bind(task_id)
+            .bind(blocker_id)
+            .bind(owner)
+            .execute(self.db_ref())
+            .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Select the live tasks a task is blocked by and the live tasks it
+    /// blocks, leaving out tasks in stories the member can't see.
+    pub async fn select_task_dependencies(
+        &self,
+        member: &str,
+        task_id: Uuid,
+    ) -> Result<TaskDependencies> {
+        log::debug!("Repo::select_task_dependencies: {}, {}", member, &task_id);
+
+        let owned = story_member(2);
+        let sql = format!(
+            r#"
+            SELECT {TASK_COLUMNS}
+            FROM tasks
+            WHERE id IN (SELECT blocker_id FROM task_dependencies WHERE task_id = $1)
+            AND {owned} AND deleted_at IS NULL
+            ORDER BY created_at ASC, id ASC
+        "#
+        );
+        let blocked_by = sqlx::query_as(&sql)
+            .bind(task_id)
+            .bind(member)
+            .fetch_all(self.db_ref())
+            .await?;
+
+        let sql = format!(
+            r#"
+            SELECT {TASK_COLUMNS}
+            FROM tasks
+            WHERE id IN (SELECT task_id FROM task_dependencies WHERE blocker_id = $1)
+            AND {owned} AND deleted_at IS NULL
+            ORDER BY created_at ASC, id ASC
+        "#
+        );
+        let blocking = sqlx::query_as(&sql)
+            .bind(task_id)
+            .bind(member)
+            .fetch_all(self.db_ref())
+            .await?;
+
+        Ok(TaskDependencies {
+            blocked_by,
+            blocking,
+        })
+    }
+
+    /// Lock an owner's live task row for the rest of the transaction.
+    async fn lock_task(tx: &mut PgConnection, owner: &str, task_id: Uuid) -> Result<bool> {
+        let owned = story_owner(2);
+        let sql = format!(
+            "SELECT id FROM tasks WHERE id = $1 AND {owned} AND deleted_at IS NULL FOR UPDATE"
+        );
+        let task: Option<Uuid> = sqlx::query_scalar(&sql)
+            .bind(task_id)
+            .bind(owner)
+            .fetch_optional(&mut *tx)
+            .await?;
+
+        Ok(task.is_some())
+    }
+
+    /// Get a checklist item of an owner's task by id
+    pub async fn get_checklist_item(&self, owner: &str, item_id: Uuid) -> Result<ChecklistItem> {
+        log::debug!("Repo::get_checklist_item: {}, {}", owner, &item_id);
+
+        let owned = task_owner(2);
+        let sql = format!(
+            r#"
+            SELECT {ITEM_COLUMNS}
+            FROM checklist_items
+            WHERE id = $1 AND {owned} AND deleted_at IS NULL
+        "#
+        );
+
+        let item = sqlx::query_as(&sql)
+            .bind(item_id)
+            .bind(owner)
+            .fetch_optional(self.db_ref())
+            .await?;
+
+        item.ok_or_else(|| Error::NotFoundError {
+            message: format!("checklist item not found: {}", item_id),
+        })
+    }
+
+    /// Select the checklist items of an owner's task, in manual order.
+    pub async fn select_checklist(&self, owner: &str, task_id: Uuid) -> Result<Vec<ChecklistItem>> {
+        log::debug!("Repo::select_checklist: {}, {}", owner, &task_id);
+
+        let owned = task_owner(2);
+        let sql = format!(
+            r#"
+            SELECT {ITEM_COLUMNS}
+            FROM checklist_items
+            WHERE task_id = $1 AND {owned} AND deleted_at IS NULL
+            ORDER BY rank ASC, created_at ASC, id ASC
+        "#
+        );
+
+        let items = sqlx::query_as(&sql)
+            .bind(task_id)
+            .bind(owner)
+            .fetch_all(self.db_ref())
+            .await?;
+
+        Ok(items)
+    }
+
+    /// Append a checklist item to an owner's live task.
+    pub async fn insert_checklist_item(
+        &self,
+        owner: &str,
+        task_id: Uuid,
+        name: String,
+    ) -> Result<ChecklistItem> {
+        log::debug!(
+            "Repo::insert_checklist_item: {}, {}, {}",
+            owner,
+            &task_id,
+            &name
+        );
+
+        let mut tx = self.db.begin().await?;
+
+        if !Self::lock_task(&mut tx, owner, task_id).await? {
+            return Err(Error::NotFoundError {
+                message: format!("task not found: {}", task_id),
+            });
+        }
+
+        let last = Self::select_last_rank(&mut tx, &ITEM_RANKS, task_id).await?;
+
+        let sql = format!(
+            r#"
+            INSERT INTO checklist_items (task_id, name, rank)
+            VALUES ($1, $2, $3)
+            RETURNING {ITEM_COLUMNS}
+        "#
+        );
+
+        let item = sqlx::query_as(&sql)
+            .bind(task_id)
+            .bind(&name)
+            .bind(rank::between(last.as_deref(), None))
+            .fetch_one(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+
+        Ok(item)
+    }
+
+    /// Mark a checklist item of an owner's task done or not done.
+    pub async fn update_checklist_item_done(
+        &self,
+        owner: &str,
+        item_id: Uuid,
+        done: bool,
+    ) -> Result<Option<ChecklistItem>> {
+        log::debug!(
+            "Repo::update_checklist_item_done: {}, {}, {}",
+            owner,
+            &item_id,
+            done
+        );
+
+        let owned = task_owner(3);
+        let sql = format!(
+            r#"
+            UPDATE checklist_items SET done = $2, updated_at = now()
+            WHERE id = $1 AND {owned} AND deleted_at IS NULL
+            RETURNING {ITEM_COLUMNS}
+        "#
+        );
+
+        let item = sqlx::query_as(&sql)
+            .bind(item_id)
+            .bind(done)
+            .bind(owner)
+            .fetch_optional(self.db_ref())
+            .await?;
+
+        Ok(item)
+    }
+
+    /// Move a checklist item before or after a sibling item of the same task.
+    pub async fn update_checklist_item_rank(
+        &self,
+        owner: &str,
+        item_id: Uuid,
+        placement: Placement,
+    ) -> Result<u64> {
+        log::debug!(
+            "Repo::update_checklist_item_rank: {}, {}, {:?}",
+            owner,
+            &item_id,
+            &placement
+        );
+
+        let mut tx = self.db.begin().await?;
+
+        // Lock the task so moves within a checklist are serialized.
+        let sql = "SELECT task_id FROM checklist_items WHERE id = $1 AND deleted_at IS NULL";
+        let task_id: Option<Uuid> = sqlx::query_scalar(sql)
+            .bind(item_id)
+            .fetch_optional(&mut *tx)
+            .await?;
+
+        let task_id = match task_id {
+            Some(task_id) if Self::lock_task(&mut tx, owner, task_id).await? => task_id,
+            _ => return Ok(0),
+        };
+
+        let sibling = placement.sibling();
+        let sql = "SELECT task_id FROM checklist_items WHERE id = $1 AND deleted_at IS NULL";
+        let sibling_task: Option<Uuid> = sqlx::query_scalar(sql)
+            .bind(sibling)
+            .fetch_optional(&mut *tx)
+            .await?;
+
+        match sibling_task {
+            None => {
+                return Err(Error::NotFoundError {
+                    message: format!("checklist item not found: {}", sibling),
+                })
+            }
+            Some(id) if id != task_id => {
+                return Err(Error::FailedPrecondition {
+                    message: format!("checklist item {} is not in task {}", sibling, task_id),
+                })
+            }
+            _ => {}
+        }
+
+        let mut rank = Self::rank_at(&mut tx, &ITEM_RANKS, task_id, item_id, placement).await?;
+        if rank.len() > MAX_RANK_LEN {
+            Self::respace_ranks(&mut tx, &ITEM_RANKS, task_id).await?;
+            rank = Self::rank_at(&mut tx, &ITEM_RANKS, task_id, item_id, placement).await?;
+        }
+
+        let sql = "UPDATE checklist_items SET rank = $2, updated_at = now() WHERE id = $1";
+        let result = sqlx::query(sql)
+            .bind(item_id)
+            .bind(&rank)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Delete a checklist item. Items are removed outright; only items
+    /// deleted along with their task can be restored.
+    pub async fn delete_checklist_item(&self, owner: &str, item_id: Uuid) -> Result<u64> {
+        log::debug!("Repo::delete_checklist_item: {}, {}", owner, &item_id);
+
+        let owned = task_owner(2);
+        let sql =
+            format!("DELETE FROM checklist_items WHERE id = $1 AND {owned} AND deleted_at IS NULL");
+        let result = sqlx::query(&sql)
+            .bind(item_id)
+            .bind(owner)
+            .execute(self.db_ref())
+            .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Add a reminder to an owner's live task. Reminders relative to the due
+    /// date need a task with a due date.
+    pub async fn insert_reminder(
+        &self,
+        owner: &str,
+        task_id: Uuid,
+        trigger: ReminderTrigger,
+    ) -> Result<Reminder> {
+        log::debug!(
+            "Repo::insert_reminder: {}, {}, {:?}",
+            owner,
+            &task_id,
+            &trigger
+        );
+
+        let sql = r#"
+            SELECT t.due_at FROM tasks t
+            JOIN stories s ON s.id = t.story_id AND s.deleted_at IS NULL
+            WHERE t.id = $1 AND s.owner = $2 AND t.deleted_at IS NULL
+        "#;
+        let due_at: Option<Option<DateTime<Utc>>> = sqlx::query_scalar(sql)
+            .bind(task_id)
+            .bind(owner)
+            .fetch_optional(self.db_ref())
+            .await?;
+
+        let (remind_at, offset_secs) = match (trigger, due_at) {
+            (_, None) => {
+                return Err(Error::NotFoundError {
+                    message: format!("task not found: {}", task_id),
+                })
+            }
+            (ReminderTrigger::At(at), _) => (Some(at), None),
+            (ReminderTrigger::BeforeDue(_), Some(None)) => {
+                return Err(Error::FailedPrecondition {
+                    message: format!("task {} has no due date", task_id),
+                })
+            }
+            (ReminderTrigger::BeforeDue(offset), _) => (None, Some(offset.num_seconds())),
+        };
+
+        let sql = format!(
+            r#"
+            INSERT INTO reminders (task_id, remind_at, offset_secs)
+            VALUES ($1, $2, $3)
+            RETURNING {REMINDER_COLUMNS}
+        "#
+        );
+
+        let reminder = sqlx::query_as(&sql)
+            .bind(task_id)
+            .bind(remind_at)
+            .bind(offset_secs)
+            .fetch_one(self.db_ref())
+            .await?;
+
+        Ok(reminder)
+    }
+
+    /// Select the reminders of an owner's task, soonest first.
+    pub async fn select_reminders(&self, owner: &str, task_id: Uuid) -> Result<Vec<Reminder>> {
+        log::debug!("Repo::select_reminders: {}, {}", owner, &task_id);
+
+        let owned = task_owner(2);
+        let sql = format!(
+            r#"
+            SELECT {REMINDER_COLUMNS}
+            FROM reminders
+            WHERE task_id = $1 AND {owned}
+            ORDER BY fire_at ASC NULLS LAST, created_at ASC, id ASC
+        "#
+        );
+
+        let reminders = sqlx::query_as(&sql)
+            .bind(task_id)
+            .bind(owner)
+            .fetch_all(self.db_ref())
+            .await?;
+
+        Ok(reminders)
+    }
+
+    /// Delete a reminder from an owner's task.
+    pub async fn delete_reminder(&self, owner: &str, reminder_id: Uuid) -> Result<u64> {
+        log::debug!("Repo::delete_reminder: {}, {}", owner, &reminder_id);
+
+        let owned = task_owner(2);
+        let sql = format!("DELETE FROM reminders WHERE id = $1 AND {owned}");
+        let result = sqlx::query(&sql)
+            .bind(reminder_id)
+            .bind(owner)
+            .execute(self.db_ref())
+            .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Insert a minted API key for an owner.
+    pub async fn insert_api_key(
+        &self,
+        owner: &str,
+        name: String,
+        key: &MintedKey,
+        expires_at: Option<DateTime<Utc>>,
+    ) -> Result<ApiKey> {
+        log::debug!(
+            "Repo::insert_api_key: {}, {}, {}",
+            owner,
+            &name,
+            &key.key_id
+        );
+
+        let sql = format!(
+            r#"
+            INSERT INTO api_keys (id, owner, name, salt, key_hash, expires_at)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            RETURNING {API_KEY_COLUMNS}
+        "#
+        );
+
+        let api_key = sqlx::query_as(&sql)
+            .bind(key.key_id)
+            .bind(owner)
+            .bind(&name)
+            .bind(&key.salt)
+            .bind(&key.key_hash)
+            .bind(expires_at)
+            .fetch_one(self.db_ref())
+            .await?;
+
+        Ok(api_key)
+    }
+
+    /// Revoke an API key. Revoking a revoked key keeps the original revocation time.
+    pub async fn revoke_api_key(&self, key_id: Uuid) -> Result<Option<ApiKey>> {
+        log::debug!("Repo::revoke_api_key: {}", &key_id);
+
+        let sql = format!(
+            r#"
+            UPDATE api_keys SET revoked_at = COALESCE(revoked_at, now())
+            WHERE id = $1
+            RETURNING {API_KEY_COLUMNS}
+        "#
+        );
+
+        let api_key = sqlx::query_as(&sql)
+            .bind(key_id)
+            .fetch_optional(self.db_ref())
+            .await?;
+
+        Ok(api_key)
+    }
+
+    /// Select a member's role in the story a scope belongs to, and the story
+    /// owner. `None` when the member neither owns nor is a member of the story.
+    pub async fn select_access(&self, member: &str, scope: StoryScope) -> Result<Option<Access>> {
+        log::debug!("Repo::select_access: {}, {:?}", member, &scope);
+
+        let story = scope_story(scope, 1);
+        let sql = format!(
+            r#"
+            SELECT s.owner, CASE WHEN s.owner = $2 THEN 'owner' ELSE m.role END AS role
+            FROM stories s
+            LEFT JOIN story_members m ON m.story_id = s.id AND m.member = $2
+            WHERE s.id = ({story}) AND (s.owner = $2 OR m.role IS NOT NULL)
+        "#
+        );
+
+        let row = sqlx::query(&sql)
+            .bind(scope.id())
+            .bind(member)
+            .fetch_optional(self.db_ref())
+            .await?;
+
+        row.map(|row| {
+            let role: String = row.try_get("role")?;
+            Ok(Access {
+                owner: row.try_get("owner")?,
+                role: Role::try_from(role).map_err(|message| Error::InternalError { message })?,
+            })
+        })
+        .transpose()
+    }
+
+    /// Share a live story with a member, or change their role. Returns `None`
+    /// when the story doesn't exist or was deleted.
+    pub async fn upsert_story_member(
+        &self,
+        story_id: Uuid,
+        member: &str,
+        role: Role,
+    ) -> Result<Option<StoryMember>> {
+        log::debug!(
+            "Repo::upsert_story_member: {}, {}, {}",
+            &story_id,
+            member,
+            role
+        );
+
+        let sql = format!(
+            r#"
+            INSERT INTO story_members (story_id, member, role)
+            SELECT id, $2, $3 FROM stories WHERE id = $1 AND deleted_at IS NULL
+            ON CONFLICT (story_id, member)
+            DO UPDATE SET role = excluded.role, updated_at = now()
+            RETURNING {MEMBER_COLUMNS}
+        "#
+        );
+
+        let member = sqlx::query_as(&sql)
+            .bind(story_id)
+            .bind(member)
+            .bind(role.to_string())
+            .fetch_optional(self.db_ref())
+            .await?;
+
+        Ok(member)
+    }
+
+    /// Select the members of a story, in the order they were added.
+    pub async fn select_story_members(&self, story_id: Uuid) -> Result<Vec<StoryMember>> {
+        log::debug!("Repo::select_story_members: {}", &story_id);
+
+        let sql = format!(
+            r#"
+            SELECT {MEMBER_COLUMNS}
+            FROM story_members
+            WHERE story_id = $1
+            ORDER BY created_at ASC, member ASC
+        "#
+        );
+
+        let members = sqlx::query_as(&sql)
+            .bind(story_id)
+            .fetch_all(self.db_ref())
+            .await?;
+
+        Ok(members)
+    }
+
+    /// Stop sharing a story with a member.
+    pub async fn delete_story_member(&self, story_id: Uuid, member: &str) -> Result<u64> {
+        log::debug!("Repo::delete_story_member: {}, {}", &story_id, member);
+
+        let sql = "DELETE FROM story_members WHERE story_id = $1 AND member = $2";
+        let result = sqlx::query(sql)
+            .bind(story_id)
+            .bind(member)
+            .execute(self.db_ref())
+            .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Insert a share link for a live story. Returns `None` when the story
+    /// doesn't exist or was deleted.
+    pub async fn insert_share_link(
+        &self,
+        story_id: Uuid,
+        created_by: &str,
+        token_hash: &[u8],
+        expires_at: Option<DateTime<Utc>>,
+    ) -> Result<Option<ShareLink>> {
+        log::debug!("Repo::insert_share_link: {}, {}", &story_id, created_by);
+
+        let sql = format!(
+            r#"
+            INSERT INTO share_links (story_id, created_by, token_hash, expires_at)
+            SELECT id, $2, $3, $4 FROM stories WHERE id = $1 AND deleted_at IS NULL
+            RETURNING {SHARE_LINK_COLUMNS}
+        "#
+        );
+
+        let link = sqlx::query_as(&sql)
+            .bind(story_id)
+            .bind(created_by)
+            .bind(token_hash)
+            .bind(expires_at)
+            .fetch_optional(self.db_ref())
+            .await?;
+
+        Ok(link)
+    }
+
+    /// Select the share links of a story, newest first.
+    pub async fn select_share_links(&self, story_id: Uuid) -> Result<Vec<ShareLink>> {
+        log::debug!("Repo::select_share_links: {}", &story_id);
+
+        let sql = format!(
+            r#"
+            SELECT {SHARE_LINK_COLUMNS}
+            FROM share_links
+            WHERE story_id = $1
+            ORDER BY created_at DESC, id ASC
+        "#
+        );
+
+        let links = sqlx::query_as(&sql)
+            .bind(story_id)
+            .fetch_all(self.db_ref())
+            .await?;
+
+        Ok(links)
+    }
+
+    /// Revoke a share link. Revoking a revoked link keeps the original revocation time.
+    pub async fn revoke_share_link(&self, link_id: Uuid) -> Result<Option<ShareLink>> {
+        log::debug!("Repo::revoke_share_link: {}", &link_id);
+
+        let sql = format!(
+            r#"
+            UPDATE share_links SET revoked_at = COALESCE(revoked_at, now())
+            WHERE id = $1
+            RETURNING {SHARE_LINK_COLUMNS}
+        "#
+        );
+
+        let link = sqlx::query_as(&sql)
+            .bind(link_id)
+            .fetch_optional(self.db_ref())
+            .await?;
+
+        Ok(link)
+    }
+
+    /// Record an access through the share link with a token hash, returning
+    /// the link and its story owner. Returns `None` for unknown, revoked or
+    /// expired links, and for links to deleted stories.
+    pub async fn access_share_link(
+        &self,
+        token_hash: &[u8],
+    ) -> Result<Option<(ShareLink, String)>> {
+        log::debug!("Repo::access_share_link");
+
+        let sql = format!(
+            r#"
+            WITH share_links AS (
+                UPDATE share_links l
+                SET access_count = l.access_count + 1, last_accessed_at = now()
+                FROM stories s
+                WHERE l.token_hash = $1 AND s.id = l.story_id AND s.deleted_at IS NULL
+                AND l.revoked_at IS NULL AND (l.expires_at IS NULL OR l.expires_at > now())
+                RETURNING l.*, s.owner
+            )
+            SELECT {SHARE_LINK_COLUMNS}, owner FROM share_links
+        "#
+        );
+
+        let row = sqlx::query(&sql)
+            .bind(token_hash)
+            .fetch_optional(self.db_ref())
+            .await?;
+
+        row.map(|row| Ok((ShareLink::from_row(&row)?, row.try_get("owner")?)))
+            .transpose()
     }
 }
@@ -0,0 +1,51 @@
+//! Tokens for read-only share links.
+//!
+//! A token is 32 random bytes, base64url encoded. Only its SHA-256 hash is
+//! stored; tokens carry enough entropy that the hash needs no salt, which lets
+//! a token be looked up by its hash.
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use rand::RngCore;
+use sha2::{Digest, Sha256};
+
+/// Random bytes in a share token.
+const TOKEN_LEN: usize = 32;
+
+/// A newly minted share token and the hash stored for it.
+pub struct ShareToken {
+    pub token: String,
+    pub token_hash: Vec<u8>,
+}
+
+impl ShareToken {
+    pub fn generate() -> Self {
+        let mut bytes = [0u8; TOKEN_LEN];
+        rand::thread_rng().fill_bytes(&mut bytes);
+        let token = URL_SAFE_NO_PAD.encode(bytes);
+        Self {
+            token_hash: hash(&token),
+            token,
+        }
+    }
+}
+
+/// The hash a token is stored and looked up by.
+pub fn hash(token: &str) -> Vec<u8> {
+    Sha256::digest(token.as_bytes()).to_vec()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generated_tokens_are_unique() {
+        let a = ShareToken::generate();
+        let b = ShareToken::generate();
+        assert_ne!(a.token, b.token);
+        assert_eq!(a.token.len(), 43);
+        assert_eq!(hash(&a.token), a.token_hash);
+        assert_ne!(hash(&b.token), a.token_hash);
+    }
+}
@@ -15,6 +15,51 @@ impl Validate {
         Ok(value)
     }
 
+    /// Ensure a string is no longer than a maximum number of characters.
+    pub fn max_length(value: &str, max: usize, param: &str) -> Result<String> {
+        let length = value.chars().count();
+        if length > max {
+            return Err(Error::InvalidArgument {
+                message: format!("{} is too long: {} > {} characters", param, length, max),
+            });
+        }
+        Ok(value.to_string())
+    }
+
+    /// Ensure a field mask is non-empty and only names supported paths.
+    pub fn update_mask(paths: &[String], supported: &[&str]) -> Result<Vec<String>> {
+        if paths.is_empty() {
+            return Err(Error::InvalidArgument {
+                message: "empty update_mask".to_string(),
+            });
+        }
+        paths
+            .iter()
+            .map(|path| {
+                let path = path.trim();
+                if supported.contains(&path) {
+                    Ok(path.to_string())
+                } else {
+                    Err(Error::InvalidArgument {
+                        message: format!("unsupported update_mask path: {}", path),
+                    })
+                }
+            })
+            .collect()
+    }
+
+    /// Ensure a color is a hex RGB value like `#1a2b3c`, normalized to lowercase.
+    pub fn color(value: &str) -> Result<String> {
+        let value = value.trim().to_lowercase();
+        let hex = value.strip_prefix('#').unwrap_or("");
+        if hex.len() != 6 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(Error::InvalidArgument {
+                message: format!("invalid color: {}", value),
+            });
+        }
+        Ok(value)
+    }
+
     /// Ensure a uuid value can be created from a string
     pub fn validate_uuid(value: &str) -> Result<Uuid> {
         let value = value.trim().to_lowercase();
@@ -41,6 +86,40 @@ mod tests {
         assert_eq!(error.to_string(), "invalid argument: empty string: 2spaces");
     }
 
+    #[test]
+    fn max_length_success() {
+        assert_eq!(Validate::max_length("ünï", 3, "text").unwrap(), "ünï");
+    }
+
+    #[test]
+    fn max_length_fail() {
+        let error = Validate::max_length("four", 3, "text").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "invalid argument: text is too long: 4 > 3 characters"
+        );
+    }
+
+    #[test]
+    fn update_mask_success() {
+        let paths = vec![" name ".to_string()];
+        let result = Validate::update_mask(&paths, &["name"]).unwrap();
+        assert_eq!(result, vec!["name"]);
+    }
+
+    #[test]
+    fn update_mask_fail() {
+        let error = Validate::update_mask(&[], &["name"]).unwrap_err();
+        assert_eq!(error.to_string(), "invalid argument: empty update_mask");
+
+        let paths = vec!["owner".to_string()];
+        let error = Validate::update_mask(&paths, &["name"]).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "invalid argument: unsupported update_mask path: owner"
+        );
+    }
+
     #[test]
     fn validate_uuid_success() {
         let input = "  4ac0160a-b132-440e-9cdf-135d7a91d6dc  ";
@@ -53,4 +132,19 @@ mod tests {
         let error = Validate::validate_uuid("4ac0160a").unwrap_err();
         assert!(error.to_string().starts_with("invalid argument"));
     }
+
+    #[test]
+    fn color_success() {
+        assert_eq!(Validate::color(" #1A2b3C ").unwrap(), "#1a2b3c");
+    }
+
+    #[test]
+    fn color_fail() {
+        for value in ["", "1a2b3c", "#1a2b3", "#1a2b3g", "#1a2b3c4"] {
+            let error = Validate::color(value).unwrap_err();
+            assert!(error
+                .to_string()
+                .starts_with("invalid argument: invalid color"));
+        }
+    }
 }